@@ -2,15 +2,14 @@
 // bench is buffer centric
 
 use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
-use hpc_core::{GpuBuffer, Queued, Ready};
+use hpc_core::{
+    launch, GpuBuffer, GpuContext, InFlightSet, Input, NdRange, Output, PingPong, PooledInput, PooledOutput, Queued, Ready,
+};
 use bytemuck::cast_slice;
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
-    context::Context,
+    command_queue::CL_QUEUE_PROFILING_ENABLE,
     memory::{Buffer, CL_MEM_READ_WRITE},
-    device::{Device, CL_DEVICE_TYPE_GPU},
     kernel::Kernel,
-    platform::get_platforms,
     program::Program,
     types::CL_BLOCKING,
 };
@@ -20,7 +19,6 @@ use std::time::Duration;
 
 const NX: usize = 1024;
 const NY: usize = 1024;
-const N_BYTES: usize = NX * NY * std::mem::size_of::<f32>();
 const N_ITERS: usize = 10;
 
 fn bench_stencil(c: &mut Criterion) {
@@ -32,25 +30,23 @@ fn bench_stencil(c: &mut Criterion) {
     g.bench_function("raw_jacobi_1024x1024_10iter_fair", |b| {
         b.iter_batched(
             || {
-                let platform = get_platforms().unwrap().remove(0);
-                let dev_id   = platform.get_devices(CL_DEVICE_TYPE_GPU).unwrap()[0];
-                let device   = Device::new(dev_id);
-                let ctx      = Context::from_device(&device).unwrap();
-                let queue    = CommandQueue::create(&ctx, device.id(), CL_QUEUE_PROFILING_ENABLE).unwrap();
+                let gpu      = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build().unwrap();
+                let (ctx, queue) = (gpu.context(), gpu.queue());
                 let src      = include_str!("../examples/stencil.cl");
-                let program  = Program::create_and_build_from_source(&ctx, src, "").unwrap();
+                let program  = Program::create_and_build_from_source(ctx, src, "").unwrap();
                 let kern     = Kernel::create(&program, "jacobi").unwrap();
 
                
-                let mut buf_src = Buffer::<f32>::create(&ctx, CL_MEM_READ_WRITE, NX*NY, ptr::null_mut()).unwrap();
+                let mut buf_src = Buffer::<f32>::create(ctx, CL_MEM_READ_WRITE, NX*NY, ptr::null_mut()).unwrap();
                 queue.enqueue_write_buffer(&mut buf_src, CL_BLOCKING, 0, cast_slice(&vec![1.0_f32; NX*NY]), &[]).unwrap();
                 
-                (ctx, queue, kern, buf_src)
+                (gpu, kern, buf_src)
             },
-            |(ctx, queue, kern, mut src_buf)| {
+            |(gpu, kern, mut src_buf)| {
+                let (ctx, queue) = (gpu.context(), gpu.queue());
                 
                 for _ in 0..N_ITERS {
-                    let mut dst_buf = Buffer::<f32>::create(&ctx, CL_MEM_READ_WRITE, NX*NY, ptr::null_mut()).unwrap();
+                    let dst_buf = Buffer::<f32>::create(ctx, CL_MEM_READ_WRITE, NX*NY, ptr::null_mut()).unwrap();
                     
                     kern.set_arg(0, &src_buf).unwrap();
                     kern.set_arg(1, &dst_buf).unwrap();
@@ -76,40 +72,118 @@ fn bench_stencil(c: &mut Criterion) {
     g.bench_function("wrapper_jacobi_1024x1024_10iter", |b| {
         b.iter_batched(
             || {
-                let platform  = get_platforms().unwrap().remove(0);
-                let dev_id    = platform.get_devices(CL_DEVICE_TYPE_GPU).unwrap()[0];
-                let device    = Device::new(dev_id);
-                let context   = Context::from_device(&device).unwrap();
-                let queue     = CommandQueue::create(&context, device.id(), CL_QUEUE_PROFILING_ENABLE).unwrap();
+                let gpu       = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build().unwrap();
+                let (context, queue) = (gpu.context(), gpu.queue());
 
                 let src      = include_str!("../examples/stencil.cl");
-                let program  = Program::create_and_build_from_source(&context, src, "").unwrap();
+                let program  = Program::create_and_build_from_source(context, src, "").unwrap();
                 let kern     = Kernel::create(&program, "jacobi").unwrap();
 
                 let init = vec![1.0_f32; NX * NY];
-                let (ping_buf, g) = GpuBuffer::<Queued>::new(&context, N_BYTES).unwrap()
-                    .enqueue_write(&queue, cast_slice(&init)).unwrap();
-                let ping_ready: GpuBuffer<Ready> = ping_buf.into_ready(g);
-
-                (context, queue, kern, ping_ready)
+                // SAFETY: Host-Transfers — jeder Guard wird per into_ready/wait verbraucht, keiner geleakt
+                let (ping_buf, g) = unsafe {
+                    GpuBuffer::<f32, Queued>::new(context, NX * NY).unwrap()
+                        .enqueue_write(queue, &init)
+                }.unwrap();
+                let ping_ready: GpuBuffer<f32, Ready> = ping_buf.into_ready(g);
+
+                (gpu, kern, ping_ready)
             },
-            |(context, queue, kern, mut ping)| {
+            |(gpu, kern, mut ping)| {
+                let (context, queue) = (gpu.context(), gpu.queue());
                 
                 for _ in 0..N_ITERS {
-                    let mut dst_if = GpuBuffer::<Queued>::new(&context, N_BYTES).unwrap().launch();
+                    let dst = GpuBuffer::<f32, Queued>::new(context, NX * NY).unwrap();
 
-                    kern.set_arg(0, ping.raw()).unwrap();
-                    kern.set_arg(1, dst_if.raw_mut()).unwrap();
                     kern.set_arg(2, &(NX as i32)).unwrap();
                     kern.set_arg(3, &(NY as i32)).unwrap();
 
-                    let global = [NX, NY, 1];
-                    let evt = queue
-                        .enqueue_nd_range_kernel(kern.get(), 2, std::ptr::null(), global.as_ptr(), std::ptr::null(), &[])
+                    let (k_if, g) = launch(queue, &kern, &NdRange::d2(NX, NY), (Input(0, ping), Output(1, dst)))
+                        .unwrap();
+
+                    let (_src, ready_dst) = k_if.into_ready(g);
+                    ping = ready_dst;
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Ping-Pong: zwei feste Buffer, Rollentausch statt Allokation pro Iteration
+    g.bench_function("wrapper_jacobi_pingpong_1024x1024_10iter", |b| {
+        b.iter_batched(
+            || {
+                let gpu       = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build().unwrap();
+                let (context, queue) = (gpu.context(), gpu.queue());
+
+                let src      = include_str!("../examples/stencil.cl");
+                let program  = Program::create_and_build_from_source(context, src, "").unwrap();
+                let kern     = Kernel::create(&program, "jacobi").unwrap();
+                kern.set_arg(2, &(NX as i32)).unwrap();
+                kern.set_arg(3, &(NY as i32)).unwrap();
+
+                // beide Buffer initialisieren: der Kernel schreibt den Rand nicht
+                let init = vec![1.0_f32; NX * NY];
+                let (ping, g1) = unsafe {
+                    GpuBuffer::<f32, Queued>::new(context, NX * NY).unwrap()
+                        .enqueue_write(queue, &init)
+                }.unwrap();
+                let (pong, g2) = unsafe {
+                    GpuBuffer::<f32, Queued>::new(context, NX * NY).unwrap()
+                        .enqueue_write(queue, &init)
+                }.unwrap();
+                let pp = PingPong::new(ping.into_ready(g1), pong.into_ready(g2)).unwrap();
+
+                (gpu, kern, pp)
+            },
+            |(gpu, kern, mut pp)| {
+                for _ in 0..N_ITERS {
+                    pp = pp.step(gpu.queue(), &kern, &NdRange::d2(NX, NY), [0, 1]).unwrap();
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Pool: Ziel-Buffer pro Iteration wie oben, aber aus dem BufferPool
+    // (ab der zweiten Iteration kein clCreateBuffer mehr)
+    g.bench_function("wrapper_jacobi_pooled_1024x1024_10iter", |b| {
+        b.iter_batched(
+            || {
+                let gpu       = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build().unwrap();
+                let (context, queue) = (gpu.context(), gpu.queue());
+
+                let src      = include_str!("../examples/stencil.cl");
+                let program  = Program::create_and_build_from_source(context, src, "").unwrap();
+                let kern     = Kernel::create(&program, "jacobi").unwrap();
+                kern.set_arg(2, &(NX as i32)).unwrap();
+                kern.set_arg(3, &(NY as i32)).unwrap();
+
+                let init = vec![1.0_f32; NX * NY];
+                let (ping_buf, g) = unsafe {
+                    GpuBuffer::<f32, Queued>::new(context, NX * NY).unwrap()
+                        .enqueue_write(queue, &init)
+                }.unwrap();
+
+                (gpu, kern, ping_buf.into_ready(g))
+            },
+            |(gpu, kern, ping)| {
+                let queue = gpu.queue();
+                let range = NdRange::d2(NX, NY);
+                // Budget für Quelle + Ziel
+                let pool  = gpu.pool::<f32>(2 * NX * NY * size_of::<f32>());
+
+                let dst = pool.get(NX * NY).unwrap();
+                let (k_if, g) = launch(queue, &kern, &range, (Input(0, ping), PooledOutput(1, dst))).unwrap();
+                let (_src, mut ping) = k_if.into_ready(g);
+
+                for _ in 1..N_ITERS {
+                    let dst = pool.get(NX * NY).unwrap();
+                    let (k_if, g) = launch(queue, &kern, &range, (PooledInput(0, ping), PooledOutput(1, dst)))
                         .unwrap();
-                    evt.wait().unwrap();
 
-                    let ready_dst = dst_if.complete(evt);
+                    // alte Quelle geht beim Drop zurück in den Pool
+                    let (_src, ready_dst) = k_if.into_ready(g);
                     ping = ready_dst;
                 }
             },
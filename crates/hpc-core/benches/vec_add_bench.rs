@@ -3,13 +3,8 @@
 
 
 use criterion::{Criterion, criterion_group, BenchmarkId, criterion_main};
-use hpc_core::{ClError, GpuBuffer, Queued};
-use bytemuck::cast_slice;
-use bytemuck::cast_slice_mut;
-use opencl3::{
-    context::Context, command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE}, program::Program, kernel::Kernel,
-    platform::get_platforms, device::{Device, CL_DEVICE_TYPE_GPU},
-};
+use hpc_core::{build_program, GpuBuffer, GpuContext, Queued};
+use opencl3::{command_queue::CL_QUEUE_PROFILING_ENABLE, kernel::Kernel};
 use std::time::Duration;
 
 const SIZES: &[usize] = &[
@@ -30,32 +25,30 @@ fn bench_vec_add(c: &mut Criterion) {
         group.bench_with_input(id, &elements, |b, &n| {
             // einmalige Setup-Arbeit pro Input-Größe
             // (wird nicht in die Messung einbezogen)
-            let platform = get_platforms().unwrap().remove(0);
-            let dev_id   = platform.get_devices(CL_DEVICE_TYPE_GPU).unwrap()[0];
-            let device   = Device::new(dev_id);
-            let context  = Context::from_device(&device).unwrap();
-            let queue    = CommandQueue::create(
-                &context, device.id(), CL_QUEUE_PROFILING_ENABLE,
-            ).unwrap();
+            let gpu      = GpuContext::builder()
+                .queue_properties(CL_QUEUE_PROFILING_ENABLE)
+                .build()
+                .unwrap();
+            let context  = gpu.context();
+            let queue    = gpu.queue();
 
             // Kernel laden & kompilieren
             let src     = include_str!("../examples/vec_add.cl");
-            let program = Program::create_and_build_from_source(&context, src, "")
-                .map_err(|_| ClError::Api(-3)).unwrap();
+            let program = build_program(context, src, "").unwrap();
             let kernel = Kernel::create(&program, "vec_add").unwrap();
             // Buffer anlegen
-            let size_bytes = n * std::mem::size_of::<f32>();
-            let a_buf = GpuBuffer::<Queued>::new(&context, size_bytes).unwrap();
-            let b_buf = GpuBuffer::<Queued>::new(&context, size_bytes).unwrap();
-            let out_buf = GpuBuffer::<Queued>::new(&context, size_bytes).unwrap();
+            let a_buf = GpuBuffer::<f32, Queued>::new(context, n).unwrap();
+            let b_buf = GpuBuffer::<f32, Queued>::new(context, n).unwrap();
+            let out_buf = GpuBuffer::<f32, Queued>::new(context, n).unwrap();
 
             // Host-Daten vorbereiten
             let h_a = vec![1.0_f32; n];
             let h_b = vec![2.0_f32; n];
 
             // Preload auf die GPU
-            let (a_if, g_a) = a_buf.enqueue_write(&queue, cast_slice(&h_a)).unwrap();
-            let (b_if, g_b) = b_buf.enqueue_write(&queue, cast_slice(&h_b)).unwrap();
+            // SAFETY: Host-Transfers — jeder Guard wird per into_ready/wait verbraucht, keiner geleakt
+            let (a_if, g_a) = unsafe { a_buf.enqueue_write(queue, &h_a) }.unwrap();
+            let (b_if, g_b) = unsafe { b_buf.enqueue_write(queue, &h_b) }.unwrap();
             let a_ready = a_if.into_ready(g_a);
             let b_ready = b_if.into_ready(g_b);
 
@@ -85,7 +78,7 @@ fn bench_vec_add(c: &mut Criterion) {
                     out_buf.raw(),
                     opencl3::types::CL_BLOCKING,
                     0,
-                    cast_slice_mut(&mut h_out),
+                    &mut h_out,
                     &[],
                 ).unwrap();
                 queue.finish().unwrap();
@@ -4,23 +4,18 @@
 
 use bytemuck::{cast_slice, cast_slice_mut};
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
-    context::Context,
-    device::{Device, CL_DEVICE_TYPE_GPU},
+    command_queue::CL_QUEUE_PROFILING_ENABLE,
     memory::{Buffer, CL_MEM_READ_WRITE},
-    platform::get_platforms,
     types::CL_NON_BLOCKING,
 };
-use hpc_core::ClError;
+use hpc_core::{ClError, GpuContext};
 use std::{env, time::Instant, ptr};
 
 fn main() -> Result<(), ClError> {
     // a) OpenCL Setup
-    let platform = get_platforms()?.remove(0);
-    let device_id = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
-    let device = Device::new(device_id);
-    let context = Context::from_device(&device)?;
-    let queue = CommandQueue::create(&context, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let gpu     = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build()?;
+    let context = gpu.context();
+    let queue   = gpu.queue();
 
     // b) Parameter 
     let args: Vec<String> = env::args().collect();
@@ -41,8 +36,8 @@ fn main() -> Result<(), ClError> {
     let mut host_data = vec![0.0f32; total_floats];
     let mut result_data = vec![0.0f32; total_floats];
     
-    for i in 0..total_floats {
-        host_data[i] = i as f32;
+    for (i, x) in host_data.iter_mut().enumerate() {
+        *x = i as f32;
     }
 
     // 4) GPU-Buffer allokation
@@ -50,7 +45,7 @@ fn main() -> Result<(), ClError> {
     let mut gpu_buffers: Vec<Buffer<f32>> = Vec::new();
     
     for _ in 0..num_buffers {
-        gpu_buffers.push(Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, chunk_size, ptr::null_mut())?);
+        gpu_buffers.push(Buffer::<f32>::create(context, CL_MEM_READ_WRITE, chunk_size, ptr::null_mut())?);
     }
     
     println!("✓ GPU buffers allocated");
@@ -138,7 +133,7 @@ fn main() -> Result<(), ClError> {
     
     // Pure H2D
     for iter in 0..5 {
-        let mut big_buffer = Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, total_floats, ptr::null_mut())?;
+        let mut big_buffer = Buffer::<f32>::create(context, CL_MEM_READ_WRITE, total_floats, ptr::null_mut())?;
         let start = Instant::now();
         let evt = queue.enqueue_write_buffer(&mut big_buffer, CL_NON_BLOCKING, 0, cast_slice(&host_data), &[])?;
         evt.wait()?;
@@ -151,7 +146,7 @@ fn main() -> Result<(), ClError> {
     }
     
     // Pure D2H
-    let mut big_buffer = Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, total_floats, ptr::null_mut())?;
+    let mut big_buffer = Buffer::<f32>::create(context, CL_MEM_READ_WRITE, total_floats, ptr::null_mut())?;
     let evt = queue.enqueue_write_buffer(&mut big_buffer, CL_NON_BLOCKING, 0, cast_slice(&host_data), &[])?;
     evt.wait()?;
     
@@ -4,25 +4,19 @@
 
 use bytemuck::{cast_slice, cast_slice_mut};
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE, CL_QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE},
-    context::Context,
-    device::{Device, CL_DEVICE_TYPE_GPU},
+    command_queue::{CL_QUEUE_PROFILING_ENABLE, CL_QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE},
     memory::{Buffer, CL_MEM_READ_WRITE},
-    platform::get_platforms,
     types::CL_NON_BLOCKING,
 };
-use hpc_core::ClError;
+use hpc_core::{ClError, GpuContext};
 use std::{env, time::Instant, ptr};
 
 fn main() -> Result<(), ClError> {
     // 1) OpenCL Setup mit Out-of-Order Queue
-    let platform = get_platforms()?.remove(0);
-    let device_id = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
-    let device = Device::new(device_id);
-    let context = Context::from_device(&device)?;
-    
     let queue_flags = CL_QUEUE_PROFILING_ENABLE | CL_QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE;
-    let queue = CommandQueue::create(&context, device.id(), queue_flags)?;
+    let gpu     = GpuContext::builder().queue_properties(queue_flags).build()?;
+    let context = gpu.context();
+    let queue   = gpu.queue();
 
     // 2) Parameter
     let args: Vec<String> = env::args().collect();
@@ -39,12 +33,12 @@ fn main() -> Result<(), ClError> {
              size_mb, num_buffers, (chunk_bytes as f64) / (1024.0 * 1024.0));
     println!("  {} iterations", iterations);
 
-    // 3) Host data
-    let mut host_data = vec![0.0f32; total_floats];
-    let mut result_data = vec![0.0f32; total_floats];
+    // 3) Host data - gepinnt (CL_MEM_ALLOC_HOST_PTR), DMA ohne Staging-Kopie
+    let mut host_data = gpu.pinned::<f32>(total_floats)?;
+    let mut result_data = gpu.pinned::<f32>(total_floats)?;
     
-    for i in 0..total_floats {
-        host_data[i] = i as f32;
+    for (i, x) in host_data.iter_mut().enumerate() {
+        *x = i as f32;
     }
 
     // 4) GPU-Buffer
@@ -52,7 +46,7 @@ fn main() -> Result<(), ClError> {
     let mut device_buffers: Vec<Buffer<f32>> = Vec::new();
     for _ in 0..num_buffers {
         device_buffers.push(Buffer::<f32>::create(
-            &context, CL_MEM_READ_WRITE, chunk_size, ptr::null_mut())?);
+            context, CL_MEM_READ_WRITE, chunk_size, ptr::null_mut())?);
     }
     println!("✓ GPU buffers allocated");
 
@@ -63,13 +57,13 @@ fn main() -> Result<(), ClError> {
         let start = Instant::now();
         let mut events = Vec::new();
         
-        for chunk in 0..num_buffers {
+        for (chunk, dev_buf) in device_buffers.iter_mut().enumerate() {
             let start_idx = chunk * chunk_size;
             let end_idx = start_idx + chunk_size;
             let chunk_data = &host_data[start_idx..end_idx];
             
             let evt = queue.enqueue_write_buffer(
-                &mut device_buffers[chunk],
+                dev_buf,
                 CL_NON_BLOCKING,
                 0,
                 cast_slice(chunk_data),
@@ -100,13 +94,13 @@ fn main() -> Result<(), ClError> {
         let start = Instant::now();
         let mut events = Vec::new();
         
-        for chunk in 0..num_buffers {
+        for (chunk, dev_buf) in device_buffers.iter().enumerate() {
             let start_idx = chunk * chunk_size;
             let end_idx = start_idx + chunk_size;
             let chunk_result = &mut result_data[start_idx..end_idx];
             
             let evt = queue.enqueue_read_buffer(
-                &device_buffers[chunk],
+                dev_buf,
                 CL_NON_BLOCKING,
                 0,
                 cast_slice_mut(chunk_result),
@@ -135,9 +129,9 @@ fn main() -> Result<(), ClError> {
     
     // Pure H2D
     for iter in 0..5 {
-        let mut big_buffer = Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, total_floats, ptr::null_mut())?;
+        let mut big_buffer = Buffer::<f32>::create(context, CL_MEM_READ_WRITE, total_floats, ptr::null_mut())?;
         let start = Instant::now();
-        let evt = queue.enqueue_write_buffer(&mut big_buffer, CL_NON_BLOCKING, 0, cast_slice(&host_data), &[])?;
+        let evt = queue.enqueue_write_buffer(&mut big_buffer, CL_NON_BLOCKING, 0, cast_slice(&host_data[..]), &[])?;
         evt.wait()?;
         let elapsed = start.elapsed().as_secs_f64();
         pure_h2d_time += elapsed;
@@ -148,13 +142,13 @@ fn main() -> Result<(), ClError> {
     }
     
     // Pure D2H
-    let mut big_buffer = Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, total_floats, ptr::null_mut())?;
-    let evt = queue.enqueue_write_buffer(&mut big_buffer, CL_NON_BLOCKING, 0, cast_slice(&host_data), &[])?;
+    let mut big_buffer = Buffer::<f32>::create(context, CL_MEM_READ_WRITE, total_floats, ptr::null_mut())?;
+    let evt = queue.enqueue_write_buffer(&mut big_buffer, CL_NON_BLOCKING, 0, cast_slice(&host_data[..]), &[])?;
     evt.wait()?;
     
     for iter in 0..5 {
         let start = Instant::now();
-        let evt = queue.enqueue_read_buffer(&big_buffer, CL_NON_BLOCKING, 0, cast_slice_mut(&mut result_data), &[])?;
+        let evt = queue.enqueue_read_buffer(&big_buffer, CL_NON_BLOCKING, 0, cast_slice_mut(&mut result_data[..]), &[])?;
         evt.wait()?;
         let elapsed = start.elapsed().as_secs_f64();
         pure_d2h_time += elapsed;
@@ -1,13 +1,9 @@
 // examples/bandwidth_wrapper_fixed.rs
 // 2025 - Fair bandwith with wrapper test
 
-use bytemuck::{cast_slice, cast_slice_mut};
-use hpc_core::{ClError, GpuBuffer, Queued, Ready};
+use hpc_core::{ClError, GpuContext, GpuBuffer, GuardSet, Ready};
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE, CL_QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE},
-    context::Context,
-    device::{Device, CL_DEVICE_TYPE_GPU},
-    platform::get_platforms,
+    command_queue::{CL_QUEUE_PROFILING_ENABLE, CL_QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE},
 };
 use std::{env, time::Instant};
 
@@ -15,17 +11,14 @@ use std::{env, time::Instant};
 use hpc_core::summary;
 
 #[cfg(feature = "memtrace")]
-use hpc_core::{flush_csv, TracingScope};
+use hpc_core::TracingScope;
 
 fn main() -> Result<(), ClError> {
     // 1) OpenCL Setup
-    let platform = get_platforms()?.remove(0);
-    let device_id = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
-    let device = Device::new(device_id);
-    let context = Context::from_device(&device)?;
-    
     let queue_flags = CL_QUEUE_PROFILING_ENABLE | CL_QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE;
-    let queue = CommandQueue::create(&context, device.id(), queue_flags)?;
+    let gpu     = GpuContext::builder().queue_properties(queue_flags).build()?;
+    let context = gpu.context();
+    let queue   = gpu.queue();
 
     // 2) Parameter
     let args: Vec<String> = env::args().collect();
@@ -35,7 +28,7 @@ fn main() -> Result<(), ClError> {
     
     let total_floats = (size_mb * 1024 * 1024) / 4;
     let chunk_size = total_floats / num_buffers;
-    let chunk_bytes = chunk_size * 4;
+    let chunk_bytes = chunk_size * std::mem::size_of::<f32>();
     
     println!("Testing FIXED WRAPPER bandwidth:");
     println!("  Total: {} MB, {} buffers of {:.1} MB each", 
@@ -46,114 +39,74 @@ fn main() -> Result<(), ClError> {
     let mut host_data = vec![0.0f32; total_floats];
     let mut result_data = vec![0.0f32; total_floats];
     
-    for i in 0..total_floats {
-        host_data[i] = i as f32;
+    for (i, x) in host_data.iter_mut().enumerate() {
+        *x = i as f32;
     }
 
-    // 4) GPU-Buffer NUR EINMAL allokieren (wie Raw-Version)
-    println!("Allocating {} GPU buffers...", num_buffers);
-    let mut gpu_buffers: Vec<GpuBuffer<Queued>> = Vec::new();
-    
+    // 4) EIN GPU-Buffer, pro Iteration in Sub-Buffer zerlegt (keine Re-Allokation)
+    println!("Allocating 1 GPU buffer, split into {} sub-buffers...", num_buffers);
+
     #[cfg(feature = "memtrace")]
     let _trace_scope = TracingScope::disabled();
-    
-    for _ in 0..num_buffers {
-        gpu_buffers.push(GpuBuffer::new(&context, chunk_bytes)?);
-    }
-    
-    println!("✓ GPU buffers allocated");
 
-    // 5) H2D Benchmark - faire Messung ohne Re-Allokation
+    // SAFETY: Host-Transfers — jeder Guard wird per into_ready/wait verbraucht, keiner geleakt
+    let (in_flight, guard) = unsafe { GpuBuffer::new(context, total_floats)?.enqueue_write(queue, &host_data) }?;
+    let mut device_buf: GpuBuffer<f32, Ready> = in_flight.into_ready(guard);
+
+    println!("✓ GPU buffer allocated");
+
+    // 5) H2D Benchmark - alle Chunks gleichzeitig in flight
     let mut h2d_total_time = 0.0;
     
     for iter in 0..iterations {
-        // Neue Buffer für diese Iteration (unvermeidbar wegen Type-State)
-        let mut iter_buffers: Vec<GpuBuffer<Queued>> = Vec::new();
-        for _ in 0..num_buffers {
-            iter_buffers.push(GpuBuffer::new(&context, chunk_bytes)?);
-        }
+        let (split, parts) = device_buf.split_even(num_buffers)?;
         
-        // ✅ Timer startet nach der Allokation
+        // ✅ Timer startet nach dem Split
         let start = Instant::now();
         
-        // Parallel H2D für alle Chunks
-        for (i, gpu_buf) in iter_buffers.into_iter().enumerate() {
-            let start_idx = i * chunk_size;
-            let end_idx = start_idx + chunk_size;
-            let chunk_data = &host_data[start_idx..end_idx];
-            
-            let (in_flight, guard) = gpu_buf.enqueue_write(&queue, cast_slice(chunk_data))?;
-            // Guard automatisch warten lassen
-            drop(guard);
-            drop(in_flight);
+        // alle Chunks mit einem clWaitForEvents abwarten
+        let mut pending = GuardSet::with_capacity(parts.len());
+        for (part, range) in parts.into_iter().zip(split.ranges()) {
+            pending.push(unsafe { part.enqueue_write_at(queue, 0, &host_data[range]) }?);
         }
+        let ready = pending.join_checked()?;
         
         let elapsed = start.elapsed().as_secs_f64();
         h2d_total_time += elapsed;
+        device_buf = split.join(ready)?;
         
         if iter == 0 {
             println!("First H2D (fixed wrapper): {:.3} ms", elapsed * 1000.0);
         }
     }
 
-    // 6) D2H Benchmark - Buffer vorbereiten AUSSERHALB der Messung
-    println!("Preparing buffers for D2H test...");
-    let mut prepared_buffers: Vec<GpuBuffer<Ready>> = Vec::new();
-    
-    for i in 0..num_buffers {
-        let gpu_buf = GpuBuffer::new(&context, chunk_bytes)?;
-        let start_idx = i * chunk_size;
-        let end_idx = start_idx + chunk_size;
-        let chunk_data = &host_data[start_idx..end_idx];
-        
-        let (in_flight, guard) = gpu_buf.enqueue_write(&queue, cast_slice(chunk_data))?;
-        let ready = in_flight.into_ready(guard);
-        prepared_buffers.push(ready);
-    }
-    
+    // 6) D2H Benchmark - derselbe Buffer, Chunks in disjunkte Host-Bereiche
     let mut d2h_total_time = 0.0;
     
     for iter in 0..iterations {
-        // Neue Ready-Buffer für diese Iteration
-        let mut iter_ready_buffers: Vec<GpuBuffer<Ready>> = Vec::new();
-        if iter > 0 {
-            // Für weitere Iterationen neu vorbereiten
-            for i in 0..num_buffers {
-                let gpu_buf = GpuBuffer::new(&context, chunk_bytes)?;
-                let start_idx = i * chunk_size;
-                let end_idx = start_idx + chunk_size;
-                let chunk_data = &host_data[start_idx..end_idx];
-                
-                let (in_flight, guard) = gpu_buf.enqueue_write(&queue, cast_slice(chunk_data))?;
-                let ready = in_flight.into_ready(guard);
-                iter_ready_buffers.push(ready);
-            }
-        } else {
-            iter_ready_buffers = prepared_buffers;
-            prepared_buffers = Vec::new(); // Move out
-        }
+        let (split, parts) = device_buf.split_even(num_buffers)?;
         
         // ✅ Timer startet bei reinen D2H-Transfers
         let start = Instant::now();
         
-        for (i, ready_buf) in iter_ready_buffers.into_iter().enumerate() {
-            let start_idx = i * chunk_size;
-            let end_idx = start_idx + chunk_size;
-            let chunk_result = &mut result_data[start_idx..end_idx];
-            
-            let (in_flight, guard) = ready_buf.enqueue_read(&queue, cast_slice_mut(chunk_result))?;
-            // Warten durch Guard-Drop
-            drop(guard);
-            drop(in_flight);
+        let mut pending = GuardSet::with_capacity(parts.len());
+        let mut rest = &mut result_data[..];
+        for (part, range) in parts.into_iter().zip(split.ranges()) {
+            let (chunk_result, tail) = rest.split_at_mut(range.len());
+            rest = tail;
+            pending.push(unsafe { part.enqueue_read(queue, chunk_result) }?);
         }
+        let ready = pending.join_checked()?;
         
         let elapsed = start.elapsed().as_secs_f64();
         d2h_total_time += elapsed;
+        device_buf = split.join(ready)?;
         
         if iter == 0 {
             println!("First D2H (fixed wrapper): {:.3} ms", elapsed * 1000.0);
         }
     }
+    drop(device_buf);
 
     // 7) Reine Transfer-Geschwindigkeit (ohne Allokationen)  
     println!("\nTesting pure transfer speed (single large buffer)...");
@@ -163,9 +116,9 @@ fn main() -> Result<(), ClError> {
     
     // Pure H2D - mehrere Messungen mit frischen Buffern
     for iter in 0..5 {
-        let big_buffer = GpuBuffer::new(&context, total_floats * 4)?;
+        let big_buffer = GpuBuffer::new(context, total_floats)?;
         let start = Instant::now();
-        let (in_flight, guard) = big_buffer.enqueue_write(&queue, cast_slice(&host_data))?;
+        let (in_flight, guard) = unsafe { big_buffer.enqueue_write(queue, &host_data) }?;
         drop(guard); // Warten
         drop(in_flight);
         let elapsed = start.elapsed().as_secs_f64();
@@ -179,12 +132,12 @@ fn main() -> Result<(), ClError> {
     // Pure D2H - Buffer mit Daten vorbereiten
     for iter in 0..5 {
         // Jede Iteration braucht einen frischen Buffer (wegen Move-Semantik)
-        let big_buffer = GpuBuffer::new(&context, total_floats * 4)?;
-        let (in_flight, guard) = big_buffer.enqueue_write(&queue, cast_slice(&host_data))?;
+        let big_buffer = GpuBuffer::new(context, total_floats)?;
+        let (in_flight, guard) = unsafe { big_buffer.enqueue_write(queue, &host_data) }?;
         let ready_for_d2h = in_flight.into_ready(guard);
         
         let start = Instant::now();
-        let (in_flight, guard) = ready_for_d2h.enqueue_read(&queue, cast_slice_mut(&mut result_data))?;
+        let (in_flight, guard) = unsafe { ready_for_d2h.enqueue_read(queue, &mut result_data) }?;
         drop(guard); // Warten
         drop(in_flight);
         let elapsed = start.elapsed().as_secs_f64();
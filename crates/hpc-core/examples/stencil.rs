@@ -4,8 +4,7 @@
 // 2D Jacobi-Stencil mit Safe-RustCL-Wrapper (GpuBuffer),
 // exakt 3 MemTrace-Einträge: H2D, Kernel, D2H.
 
-use bytemuck::{cast_slice, cast_slice_mut};
-use hpc_core::{ClError, GpuBuffer, Queued, Ready};
+use hpc_core::{build_program, launch, ClError, GpuContext, GpuBuffer, InFlightSet, Input, NdRange, Output, Queued, Ready};
 
 #[cfg(feature = "metrics")]
 use hpc_core::summary;
@@ -14,39 +13,34 @@ use hpc_core::summary;
 use hpc_core::{start as trace_start, Dir, flush_csv, TracingScope};
 
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
-    context::Context,
-    device::{Device, CL_DEVICE_TYPE_GPU},
+    command_queue::CL_QUEUE_PROFILING_ENABLE,
     kernel::Kernel,
-    platform::get_platforms,
-    program::Program,
 };
 
 fn main() -> Result<(), ClError> {
     // 1) Setup
-    let platform  = get_platforms()?.remove(0);
-    let device_id = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
-    let device    = Device::new(device_id);
-    let context   = Context::from_device(&device)?;
-    let queue     = CommandQueue::create(&context, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let gpu     = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build()?;
+    let context = gpu.context();
+    let queue   = gpu.queue();
 
     // 2) Params + Host data
     let args: Vec<String> = std::env::args().collect();
     let width  = args.get(1).and_then(|s| s.parse().ok()).unwrap_or(1026);
     let height = args.get(2).and_then(|s| s.parse().ok()).unwrap_or(1026);
     let total      = width * height;
+    #[cfg(feature = "memtrace")]
     let size_bytes = total * std::mem::size_of::<f32>();
     let mut h_src = vec![0.0f32; total];
     let mut h_dst = vec![0.0f32; total];
-    for i in 0..total { 
-        h_src[i] = i as f32; 
+    for (i, x) in h_src.iter_mut().enumerate() { 
+        *x = i as f32; 
     }
 
     // 3) Device buffers via wrapper
-    let src_dev = GpuBuffer::<Queued>::new(&context, size_bytes)?;
-    let dst_dev = GpuBuffer::<Queued>::new(&context, size_bytes)?;
-    let src_ready: GpuBuffer<Ready>;
-    let dst_ready: GpuBuffer<Ready>;
+    let src_dev = GpuBuffer::<f32, Queued>::new(context, total)?;
+    let dst_dev = GpuBuffer::<f32, Queued>::new(context, total)?;
+    let src_ready: GpuBuffer<f32, Ready>;
+    let dst_ready: GpuBuffer<f32, Ready>;
 
     // 4) Host→Device als ein logischer H2D-Block
     #[cfg(feature = "memtrace")]
@@ -55,9 +49,10 @@ fn main() -> Result<(), ClError> {
     #[cfg(feature = "memtrace")]
     let _scope = TracingScope::disabled(); // Deaktiviere Auto-Tracing temporär
     
-    let (si, gi) = src_dev.enqueue_write(&queue, cast_slice(&h_src))?;
+    // SAFETY: Host-Transfers — jeder Guard wird per into_ready/wait verbraucht, keiner geleakt
+    let (si, gi) = unsafe { src_dev.enqueue_write(queue, &h_src) }?;
     src_ready = si.into_ready(gi);
-    let (di, gd) = dst_dev.enqueue_write(&queue, cast_slice(&h_dst))?;
+    let (di, gd) = unsafe { dst_dev.enqueue_write(queue, &h_dst) }?;
     dst_ready = di.into_ready(gd);
     
     #[cfg(feature = "memtrace")]
@@ -66,31 +61,20 @@ fn main() -> Result<(), ClError> {
     #[cfg(feature = "memtrace")]
     tok_h2d.finish();
 
-    // 5) Kernel
-    #[cfg(feature = "memtrace")]
-    let tok_k = trace_start(Dir::Kernel, 0);
-    
+    // 5) Kernel - Auto-Tracing erfasst den Kernel-Event
     let src_cl  = include_str!("../examples/stencil.cl");
-    let program = Program::create_and_build_from_source(&context, src_cl, "")
-        .map_err(|_| ClError::Api(-3))?;
+    let program = build_program(context, src_cl, "")?;
     let kernel = Kernel::create(&program, "jacobi")?;
-    kernel.set_arg(0, src_ready.raw())?;
-    kernel.set_arg(1, dst_ready.raw())?;
     kernel.set_arg(2, &(width as i32))?;
     kernel.set_arg(3, &(height as i32))?;
-    let global = [width, height, 1];
-    queue.enqueue_nd_range_kernel(
-        kernel.get(), 2,
-        std::ptr::null(), global.as_ptr(),
-        std::ptr::null(), &[],
+    let (k_if, gk) = launch(
+        queue, &kernel, &NdRange::d2(width, height),
+        (Input(0, src_ready), Output(1, dst_ready)),
     )?;
-    queue.finish()?;
-    
-    #[cfg(feature = "memtrace")]
-    tok_k.finish();
+    let (_src_ready, dst_ready) = k_if.into_ready(gk);
 
     // 6) Device→Host (D2H) - Auto-Tracing funktioniert normal
-    let (ri, gr) = dst_ready.enqueue_read(&queue, cast_slice_mut(&mut h_dst))?;
+    let (ri, gr) = unsafe { dst_ready.enqueue_read(queue, &mut h_dst) }?;
     let _        = ri.into_ready(gr);
 
     // 7) Reports
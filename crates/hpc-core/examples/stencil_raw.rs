@@ -5,16 +5,12 @@
 
 use bytemuck::{cast_slice, cast_slice_mut};
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
-    context::Context,
-    device::{Device, CL_DEVICE_TYPE_GPU},
+    command_queue::CL_QUEUE_PROFILING_ENABLE,
     kernel::Kernel,
     memory::{Buffer, CL_MEM_READ_WRITE},
-    platform::get_platforms,
-    program::Program,
     types::CL_BLOCKING,
 };
-use hpc_core::ClError;
+use hpc_core::{build_program, ClError, GpuContext};
 
 #[cfg(feature = "metrics")]
 use hpc_core::summary;
@@ -26,11 +22,9 @@ use std::env;
 
 fn main() -> Result<(), ClError> {
     // 1) OpenCL-Setup
-    let platform  = get_platforms()?.remove(0);
-    let device_id = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
-    let device    = Device::new(device_id);
-    let context   = Context::from_device(&device)?;
-    let queue     = CommandQueue::create(&context, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let gpu     = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build()?;
+    let context = gpu.context();
+    let queue   = gpu.queue();
 
     // 2) Parameter
     let args: Vec<String> = env::args().collect();
@@ -51,8 +45,8 @@ fn main() -> Result<(), ClError> {
     }
 
     // 3) Device-Buffers
-    let mut src_buf = Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, total, std::ptr::null_mut())?;
-    let mut dst_buf = Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, total, std::ptr::null_mut())?;
+    let mut src_buf = Buffer::<f32>::create(context, CL_MEM_READ_WRITE, total, std::ptr::null_mut())?;
+    let mut dst_buf = Buffer::<f32>::create(context, CL_MEM_READ_WRITE, total, std::ptr::null_mut())?;
 
     // 4) Host→Device
     #[cfg(feature = "memtrace")]
@@ -69,8 +63,7 @@ fn main() -> Result<(), ClError> {
     let tok_k = trace_start(Dir::Kernel, 0);
 
     let src_cl  = include_str!("../examples/stencil.cl");
-    let program = Program::create_and_build_from_source(&context, src_cl, "")
-    .map_err(|_s| ClError::Api(-3))?;
+    let program = build_program(context, src_cl, "")?;
     let kernel  = Kernel::create(&program, "jacobi")?;
     kernel.set_arg(0, &src_buf)?;
     kernel.set_arg(1, &dst_buf)?;
@@ -6,16 +6,12 @@
 */
 
 
-use hpc_core::ClError;
+use hpc_core::{build_program, ClError, GpuContext};
 use bytemuck::{cast_slice, cast_slice_mut};
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
-    context::Context,
-    device::{Device, CL_DEVICE_TYPE_GPU},
+    command_queue::CL_QUEUE_PROFILING_ENABLE,
     kernel::Kernel,
     memory::{Buffer, CL_MEM_READ_WRITE},
-    platform::get_platforms,
-    program::Program,
     types::CL_BLOCKING,
 };
 use std::env;
@@ -23,12 +19,9 @@ use std::env;
 fn main() -> Result<(), ClError> {
 
     // usual setup for normale opencl3
-    let platforms = get_platforms()?;
-    let platform  = &platforms[0];
-    let device_id = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
-    let device    = Device::new(device_id);
-    let context   = Context::from_device(&device)?;
-    let queue     = CommandQueue::create(&context, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let gpu     = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build()?;
+    let context = gpu.context();
+    let queue   = gpu.queue();
 
     // parameter given as parameters
     let args: Vec<String> = env::args().collect();
@@ -46,8 +39,8 @@ fn main() -> Result<(), ClError> {
     let mut dst = vec![0.0f32; total];
 
     // Device-Puffer
-    let  mut src_buf = Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, total, std::ptr::null_mut())?;
-    let mut dst_buf = Buffer::<f32>::create(&context, CL_MEM_READ_WRITE, total, std::ptr::null_mut())?;
+    let  mut src_buf = Buffer::<f32>::create(context, CL_MEM_READ_WRITE, total, std::ptr::null_mut())?;
+    let mut dst_buf = Buffer::<f32>::create(context, CL_MEM_READ_WRITE, total, std::ptr::null_mut())?;
 
     // Write data on GPU
     queue.enqueue_write_buffer(&mut src_buf, CL_BLOCKING, 0, cast_slice(&src), &[])?;
@@ -56,8 +49,7 @@ fn main() -> Result<(), ClError> {
 
     
     let src_cl  = include_str!("./stencil.cl");
-    let program = Program::create_and_build_from_source(&context, src_cl, "")
-    .map_err(|_s| ClError::Api(-3))?;
+    let program = build_program(context, src_cl, "")?;
     let kernel  = Kernel::create(&program, "jacobi")?;
     kernel.set_arg(0, &src_buf)?;
     kernel.set_arg(1, &dst_buf)?;
@@ -74,7 +66,7 @@ fn main() -> Result<(), ClError> {
     queue.finish()?;
 
 
-    queue.enqueue_read_buffer(&mut dst_buf, CL_BLOCKING, 0, cast_slice_mut(&mut dst), &[])?;
+    queue.enqueue_read_buffer(&dst_buf, CL_BLOCKING, 0, cast_slice_mut(&mut dst), &[])?;
     queue.finish()?;
 
     // verification
@@ -3,16 +3,11 @@
 //
 // Vektoraddition mit Safe-RustCL-Wrapper (Typ-State + Metrics + MemTrace)
 
-use bytemuck::{cast_slice, cast_slice_mut};
-use hpc_core::{ClError, GpuBuffer, Queued, Ready};
+use hpc_core::{build_program, launch, ClError, GpuContext, GpuBuffer, InFlightSet, Input, NdRange, Output, Queued, Ready};
 
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
-    context::Context,
-    device::{Device, CL_DEVICE_TYPE_GPU},
+    command_queue::CL_QUEUE_PROFILING_ENABLE,
     kernel::Kernel,
-    platform::get_platforms,
-    program::Program,
 };
 
 #[cfg(feature = "metrics")]
@@ -22,47 +17,47 @@ use hpc_core::{start as trace_start, Dir, flush_csv};
 
 fn main() -> Result<(), ClError> {
     // 1) OpenCL-Setup
-    let platform   = get_platforms()?.remove(0);
-    let device_id  = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
-    let device     = Device::new(device_id);
-    let context    = Context::from_device(&device)?;
-    let queue      = CommandQueue::create(&context, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let gpu     = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build()?;
+    let context = gpu.context();
+    let queue   = gpu.queue();
 
     // 2) Host-Daten vorbereiten
     let n           = 1 << 22;                          // 4 Mi Elemente
+    #[cfg(feature = "memtrace")]
     let size_bytes  = n * std::mem::size_of::<f32>();
     let h_a         = vec![1.0_f32; n];
     let h_b         = vec![2.0_f32; n];
     let mut h_out   = vec![0.0_f32; n];
 
     // 3) Device-Puffer über Wrapper anlegen
-    let a_dev   = GpuBuffer::<Queued>::new(&context, size_bytes)?;
-    let b_dev   = GpuBuffer::<Queued>::new(&context, size_bytes)?;
-    let out_dev = GpuBuffer::<Queued>::new(&context, size_bytes)?;
+    let a_dev   = GpuBuffer::<f32, Queued>::new(context, n)?;
+    let b_dev   = GpuBuffer::<f32, Queued>::new(context, n)?;
+    let out_dev = GpuBuffer::<f32, Queued>::new(context, n)?;
 
-// error[E0463]: missing field `cl_mem` in initializer of `GpuBuffer<Ready>`
+// error[E0463]: missing field `cl_mem` in initializer of `GpuBuffer<f32, Ready>`
 
     // 4) Host→Device (A)
     #[cfg(feature = "memtrace")]
     let tok_a = trace_start(Dir::H2D, size_bytes);
-    let (a_if, guard_a) = a_dev.enqueue_write(&queue, cast_slice(&h_a))?;
-    let a_ready: GpuBuffer<Ready> = a_if.into_ready(guard_a);
+    // SAFETY: Host-Transfers — jeder Guard wird per into_ready/wait verbraucht, keiner geleakt
+    let (a_if, guard_a) = unsafe { a_dev.enqueue_write(queue, &h_a) }?;
+    let a_ready: GpuBuffer<f32, Ready> = a_if.into_ready(guard_a);
     #[cfg(feature = "memtrace")]
     tok_a.finish();
 
     // 5) Host→Device (B)
     #[cfg(feature = "memtrace")]
     let tok_b = trace_start(Dir::H2D, size_bytes);
-    let (b_if, guard_b) = b_dev.enqueue_write(&queue, cast_slice(&h_b))?;
-    let b_ready: GpuBuffer<Ready> = b_if.into_ready(guard_b);
+    let (b_if, guard_b) = unsafe { b_dev.enqueue_write(queue, &h_b) }?;
+    let b_ready: GpuBuffer<f32, Ready> = b_if.into_ready(guard_b);
     #[cfg(feature = "memtrace")]
     tok_b.finish();
 
     // 6) Host→Device (Out-Initialisierung)
     #[cfg(feature = "memtrace")]
     let tok_o = trace_start(Dir::H2D, size_bytes);
-    let (o_if, guard_o) = out_dev.enqueue_write(&queue, cast_slice(&h_out))?;
-    let out_ready: GpuBuffer<Ready> = o_if.into_ready(guard_o);
+    let (o_if, guard_o) = unsafe { out_dev.enqueue_write(queue, &h_out) }?;
+    let out_ready: GpuBuffer<f32, Ready> = o_if.into_ready(guard_o);
     #[cfg(feature = "memtrace")]
     tok_o.finish();
 
@@ -70,27 +65,21 @@ fn main() -> Result<(), ClError> {
     #[cfg(feature = "memtrace")]
     let tok_k = trace_start(Dir::Kernel, 0);
     let src     = include_str!("../examples/vec_add.cl");
-    let program = Program::create_and_build_from_source(&context, src, "")
-        .map_err(|_| ClError::Api(-3))?;
+    let program = build_program(context, src, "")?;
     let kernel  = Kernel::create(&program, "vec_add")?;
-    kernel.set_arg(0, a_ready.raw())?;
-    kernel.set_arg(1, b_ready.raw())?;
-    kernel.set_arg(2, out_ready.raw())?;
-    let global = [n, 1, 1];
-    queue.enqueue_nd_range_kernel(
-        kernel.get(), 1,
-        std::ptr::null(), global.as_ptr(),
-        std::ptr::null(), &[],
+    let (k_if, guard_k) = launch(
+        queue, &kernel, &NdRange::d1(n),
+        (Input(0, a_ready), Input(1, b_ready), Output(2, out_ready)),
     )?;
-    queue.finish()?;  // warte auf Kernel
+    let (_a, _b, out_ready) = k_if.into_ready(guard_k);  // warte auf Kernel
     #[cfg(feature = "memtrace")]
     tok_k.finish();
 
     // 8) Device→Host (Out lesen)
     #[cfg(feature = "memtrace")]
     let tok_d = trace_start(Dir::D2H, size_bytes);
-    let (read_if, guard_read) = out_ready.enqueue_read(&queue, cast_slice_mut(&mut h_out))?;
-    let _final: GpuBuffer<Ready> = read_if.into_ready(guard_read);
+    let (read_if, guard_read) = unsafe { out_ready.enqueue_read(queue, &mut h_out) }?;
+    let _final: GpuBuffer<f32, Ready> = read_if.into_ready(guard_read);
     #[cfg(feature = "memtrace")]
     tok_d.finish();
 
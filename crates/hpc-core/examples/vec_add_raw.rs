@@ -1,16 +1,12 @@
 // 2025 Thomas Bicanic – MIT License
 
 use bytemuck::{cast_slice, cast_slice_mut};
-use hpc_core::ClError;
+use hpc_core::{build_program, ClError, GpuContext};
 
 use opencl3::{
-    command_queue::{CommandQueue, CL_QUEUE_PROFILING_ENABLE},
-    context::Context,
-    device::{Device, CL_DEVICE_TYPE_GPU},
+    command_queue::CL_QUEUE_PROFILING_ENABLE,
     kernel::Kernel,
     memory::{Buffer, CL_MEM_READ_WRITE},
-    platform::get_platforms,
-    program::Program,
     types::CL_BLOCKING,
 };
 
@@ -21,11 +17,9 @@ use hpc_core::{start as trace_start, Dir, flush_csv};
 
 fn main() -> Result<(), ClError> {
     // 1. OpenCL-Setup
-    let platform   = get_platforms()?.remove(0);
-    let device_id  = platform.get_devices(CL_DEVICE_TYPE_GPU)?[0];
-    let device     = Device::new(device_id);
-    let context    = Context::from_device(&device)?;
-    let queue      = CommandQueue::create(&context, device.id(), CL_QUEUE_PROFILING_ENABLE)?;
+    let gpu     = GpuContext::builder().queue_properties(CL_QUEUE_PROFILING_ENABLE).build()?;
+    let context = gpu.context();
+    let queue   = gpu.queue();
 
     // 2. Hostdaten
     let n           = 1 << 22;                         // 4 Mi Elemente ≈ 16 MiB?
@@ -37,11 +31,11 @@ fn main() -> Result<(), ClError> {
 
     // 3. Device-Buffer anlegen
     let mut a_dev: Buffer<f32>  =
-        Buffer::create(&context, CL_MEM_READ_WRITE, n, std::ptr::null_mut())?;
+        Buffer::create(context, CL_MEM_READ_WRITE, n, std::ptr::null_mut())?;
     let mut b_dev: Buffer<f32>  =
-        Buffer::create(&context, CL_MEM_READ_WRITE, n, std::ptr::null_mut())?;
-    let mut out_dev: Buffer<f32> =
-        Buffer::create(&context, CL_MEM_READ_WRITE, n, std::ptr::null_mut())?;
+        Buffer::create(context, CL_MEM_READ_WRITE, n, std::ptr::null_mut())?;
+    let out_dev: Buffer<f32> =
+        Buffer::create(context, CL_MEM_READ_WRITE, n, std::ptr::null_mut())?;
 
     // 4. Host→Device – Kopie A (separates Token)
     #[cfg(feature = "memtrace")]
@@ -61,8 +55,7 @@ fn main() -> Result<(), ClError> {
     #[cfg(feature = "memtrace")]
     let tok_kernel = trace_start(Dir::Kernel, 0);
     let src     = include_str!("../examples/vec_add.cl");
-    let program = Program::create_and_build_from_source(&context, src, "")
-        .map_err(|_| ClError::Api(-3))?;
+    let program = build_program(context, src, "")?;
     let kernel  = Kernel::create(&program, "vec_add")?;
     kernel.set_arg(0, &a_dev)?;
     kernel.set_arg(1, &b_dev)?;
@@ -80,7 +73,7 @@ fn main() -> Result<(), ClError> {
     // 7. Device→Host – ein Token
     #[cfg(feature = "memtrace")]
     let tok_d2h = trace_start(Dir::D2H, size_bytes);
-    queue.enqueue_read_buffer(&mut out_dev, CL_BLOCKING, 0, cast_slice_mut(&mut h_out), &[])?;
+    queue.enqueue_read_buffer(&out_dev, CL_BLOCKING, 0, cast_slice_mut(&mut h_out), &[])?;
     #[cfg(feature = "memtrace")]
     tok_d2h.finish();
 
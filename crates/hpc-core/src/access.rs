@@ -0,0 +1,116 @@
+//! src/access.rs
+//
+// Zugriffs-Flags im Buffer-Typ: Kernel-Zugriff (CL_MEM_READ_ONLY /
+// WRITE_ONLY) und Host-Zugriff (CL_MEM_HOST_*) als Marker. Der Treiber
+// kann den Buffer passend platzieren, Fehlbenutzung scheitert beim Kompilieren.
+
+use crate::sealed::Sealed;
+use opencl3::{
+    memory::{
+        CL_MEM_HOST_NO_ACCESS, CL_MEM_HOST_READ_ONLY, CL_MEM_HOST_WRITE_ONLY, CL_MEM_READ_ONLY,
+        CL_MEM_READ_WRITE, CL_MEM_USE_HOST_PTR, CL_MEM_WRITE_ONLY,
+    },
+    types::cl_mem_flags,
+};
+use std::marker::PhantomData;
+
+/// `CL_MEM_*` flags of a buffer, fixed at allocation.
+pub trait Access: Sealed {
+    const FLAGS: cl_mem_flags;
+
+    // Device greift auf geliehenen Host-Speicher zu → Guards müssen warten
+    #[doc(hidden)]
+    const HOST_PTR: bool = false;
+}
+
+/// Kernel-side access ([`ReadWrite`], [`ReadOnly`], [`WriteOnly`]); the
+/// host restrictions wrap one of these.
+pub trait KernelAccess: Access {}
+
+/// Kernels may read the buffer (bindable as [`Input`](crate::Input)).
+pub trait KernelRead: Access {}
+
+/// Kernels may write the buffer (bindable as [`Output`](crate::Output)).
+pub trait KernelWrite: Access {}
+
+/// The host may upload into the buffer (`enqueue_write*`, `map_write`).
+pub trait HostWrite: Access {}
+
+/// The host may download from the buffer (`enqueue_read*`, `map_*`).
+pub trait HostRead: Access {}
+
+/// Storage is allocated by the runtime ([`new_in`](crate::GpuBuffer::new_in),
+/// [`BufferPool`](crate::BufferPool)): every access type except
+/// [`UseHostPtr`], which wraps an existing host slice.
+pub trait DeviceAlloc: Access {}
+
+/// `CL_MEM_READ_WRITE`, no host restriction (default).
+pub struct ReadWrite;
+
+/// `CL_MEM_READ_ONLY`: kernels only read.
+pub struct ReadOnly;
+
+/// `CL_MEM_WRITE_ONLY`: kernels only write.
+pub struct WriteOnly;
+
+/// `CL_MEM_HOST_NO_ACCESS`: device-only buffer, e.g. scratch space.
+pub struct HostNoAccess<K: KernelAccess = ReadWrite>(PhantomData<K>);
+
+/// `CL_MEM_HOST_WRITE_ONLY`: the host only uploads, e.g. kernel inputs.
+pub struct HostWriteOnly<K: KernelAccess = ReadWrite>(PhantomData<K>);
+
+/// `CL_MEM_HOST_READ_ONLY`: the host only downloads, e.g. kernel results.
+pub struct HostReadOnly<K: KernelAccess = ReadWrite>(PhantomData<K>);
+
+/// `CL_MEM_USE_HOST_PTR`: backed by a host slice borrowed for `'h`
+/// (zero-copy on integrated GPUs and CPU devices).
+pub struct UseHostPtr<'h, K: KernelAccess = ReadWrite>(PhantomData<(&'h mut [u8], K)>);
+
+macro_rules! kernel_access {
+    ($($name:ident = $flags:expr, read: $r:tt, write: $w:tt;)+) => {$(
+        impl Sealed for $name {}
+        impl Access for $name { const FLAGS: cl_mem_flags = $flags; }
+        impl KernelAccess for $name {}
+        impl DeviceAlloc for $name {}
+        impl HostRead for $name {}
+        impl HostWrite for $name {}
+        kernel_access!(@cap $name, $r, KernelRead);
+        kernel_access!(@cap $name, $w, KernelWrite);
+    )+};
+    (@cap $name:ident, true, $cap:ident) => { impl $cap for $name {} };
+    (@cap $name:ident, false, $cap:ident) => {};
+}
+
+kernel_access! {
+    ReadWrite = CL_MEM_READ_WRITE, read: true,  write: true;
+    ReadOnly  = CL_MEM_READ_ONLY,  read: true,  write: false;
+    WriteOnly = CL_MEM_WRITE_ONLY, read: false, write: true;
+}
+
+// Host-Einschränkungen übernehmen die Kernel-Rechte von `K`
+macro_rules! host_access {
+    ($($name:ident = $flags:expr $(, $host:ident)*;)+) => {$(
+        impl<K: KernelAccess> Sealed for $name<K> {}
+        impl<K: KernelAccess> Access for $name<K> { const FLAGS: cl_mem_flags = K::FLAGS | $flags; }
+        impl<K: KernelAccess> DeviceAlloc for $name<K> {}
+        impl<K: KernelAccess + KernelRead> KernelRead for $name<K> {}
+        impl<K: KernelAccess + KernelWrite> KernelWrite for $name<K> {}
+        $(impl<K: KernelAccess> $host for $name<K> {})*
+    )+};
+}
+
+host_access! {
+    HostNoAccess  = CL_MEM_HOST_NO_ACCESS;
+    HostWriteOnly = CL_MEM_HOST_WRITE_ONLY, HostWrite;
+    HostReadOnly  = CL_MEM_HOST_READ_ONLY, HostRead;
+}
+
+impl<K: KernelAccess> Sealed for UseHostPtr<'_, K> {}
+impl<K: KernelAccess> Access for UseHostPtr<'_, K> {
+    const FLAGS: cl_mem_flags = K::FLAGS | CL_MEM_USE_HOST_PTR;
+    const HOST_PTR: bool = true;
+}
+impl<K: KernelAccess + KernelRead> KernelRead for UseHostPtr<'_, K> {}
+impl<K: KernelAccess + KernelWrite> KernelWrite for UseHostPtr<'_, K> {}
+impl<K: KernelAccess> HostRead for UseHostPtr<'_, K> {}
+impl<K: KernelAccess> HostWrite for UseHostPtr<'_, K> {}
@@ -0,0 +1,440 @@
+//! src/backend/mock.rs
+//
+// Mock-Backend: "Device"-Speicher im Host-RAM, jede Queue hat einen
+// Worker-Thread, der Kommandos in Reihenfolge (optional verzögert) ausführt.
+// Kein OpenCL-Aufruf → läuft auf CI ohne GPU und unter Miri.
+
+use super::{Backend, Callback};
+use crate::{ClError, Rect};
+use bytemuck::Pod;
+use opencl3::event::{CL_COMPLETE, CL_SUBMITTED};
+use opencl3::types::cl_mem_flags;
+use opencl3::error_codes::{
+    CL_EXEC_STATUS_ERROR_FOR_EVENTS_IN_WAIT_LIST, CL_INVALID_VALUE, CL_MISALIGNED_SUB_BUFFER_OFFSET,
+};
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicI32, AtomicUsize, Ordering},
+        mpsc::{channel, Sender},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Host-memory backend simulating buffers, in-order queues and events.
+///
+/// `CL_MEM_USE_HOST_PTR` buffers ([`GpuBuffer::from_host`](crate::GpuBuffer::from_host))
+/// get a copy of the host slice instead of aliasing it, which the OpenCL
+/// specification permits as caching. Device writes therefore do not show up
+/// in the host slice, and host writes after creation do not reach the buffer.
+pub struct Mock;
+
+/// Mock "device"; queues created from it inherit its completion delay.
+#[derive(Clone, Debug)]
+pub struct MockContext {
+    delay: Duration,
+    align: usize,
+}
+
+impl Default for MockContext {
+    // 128 Byte wie bei gängigen GPUs (1024 Bit)
+    fn default() -> Self { Self { delay: Duration::ZERO, align: 128 } }
+}
+
+impl MockContext {
+    pub fn new() -> Self { Self::default() }
+
+    /// Every command completes `delay` after the previous one on its queue.
+    pub fn with_delay(delay: Duration) -> Self { Self { delay, ..Self::default() } }
+
+    /// Simulated `CL_DEVICE_MEM_BASE_ADDR_ALIGN` in bytes (default 128).
+    pub fn base_align(mut self, bytes: usize) -> Self {
+        self.align = bytes.max(1);
+        self
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// In-order queue executed by a worker thread; dropping it finishes
+/// all enqueued commands.
+pub struct MockQueue {
+    tx: Option<Sender<(Job, MockEvent)>>,
+    worker: Option<JoinHandle<()>>,
+    delay: Duration,
+    fail_next: AtomicI32,
+}
+
+impl MockQueue {
+    pub fn new(ctx: &MockContext) -> Self {
+        let (tx, rx) = channel::<(Job, MockEvent)>();
+        let delay = ctx.delay;
+        let worker = thread::spawn(move || {
+            for (job, evt) in rx {
+                if !delay.is_zero() { thread::sleep(delay); }
+                job();
+                evt.complete();
+            }
+        });
+        Self { tx: Some(tx), worker: Some(worker), delay, fail_next: AtomicI32::new(0) }
+    }
+
+    /// Completion delay per command.
+    pub fn delay(&self) -> Duration { self.delay }
+
+    /// Lets the next enqueued command terminate with the (negative) `code`
+    /// instead of executing.
+    pub fn fail_next(&self, code: i32) { self.fail_next.store(code, Ordering::Relaxed); }
+
+    /// Blocks until every command enqueued so far has completed.
+    pub fn finish(&self) {
+        let _ = Mock::wait(&self.submit(&[], Box::new(|| {})));
+    }
+
+    fn submit(&self, wait: &[&MockEvent], job: Job) -> MockEvent {
+        let evt = MockEvent::default();
+        let status = self.fail_next.swap(0, Ordering::Relaxed);
+        let job: Job = match status {
+            0    => job,
+            code => { let e = evt.clone(); Box::new(move || e.set_status(code)) }
+        };
+        // Abhängigkeiten (auch anderer Queues) im Worker abwarten; schlägt
+        // eine fehl, endet das Kommando wie bei OpenCL ohne Ausführung
+        let job: Job = match wait {
+            [] => job,
+            _  => {
+                let (deps, e) = (wait.iter().map(|&d| d.clone()).collect::<Vec<_>>(), evt.clone());
+                Box::new(move || match deps.iter().all(|d| Mock::wait(d).is_ok()) {
+                    true  => job(),
+                    false => e.set_status(CL_EXEC_STATUS_ERROR_FOR_EVENTS_IN_WAIT_LIST),
+                })
+            }
+        };
+        // Worker lebt, solange `tx` existiert
+        self.tx.as_ref().expect("queue alive").send((job, evt.clone())).expect("mock worker alive");
+        evt
+    }
+}
+
+impl Drop for MockQueue {
+    fn drop(&mut self) {
+        drop(self.tx.take());
+        if let Some(w) = self.worker.take() { let _ = w.join(); }
+    }
+}
+
+/// Mock device buffer; sub-buffers share the parent's storage.
+pub struct MockMem<T> {
+    storage: Arc<MockStorage>,
+    offset: usize,
+    id: usize,
+    // Host-Kopie während eines Maps (ausgerichtet für `T`)
+    staging: Box<[T]>,
+}
+
+// Speicher eines eigenständigen Buffers, geteilt mit seinen Sub-Buffern
+struct MockStorage {
+    bytes: Mutex<Vec<u8>>,
+    align: usize,
+    // ID des eigenständigen Buffers = Eltern-ID der Sub-Buffer
+    id: usize,
+}
+
+// Fortlaufende Objekt-IDs, analog zu den cl_mem-Handles
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+#[derive(Default)]
+struct EventState {
+    status: Option<i32>,
+    callbacks: Vec<Callback>,
+}
+
+/// Mock event, completed by the queue's worker thread.
+#[derive(Clone, Default)]
+pub struct MockEvent(Arc<(Mutex<EventState>, Condvar)>);
+
+impl MockEvent {
+    fn state(&self) -> MutexGuard<'_, EventState> {
+        self.0.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Status nur beim ersten Aufruf setzen (fail_next kommt vor `complete`)
+    fn set_status(&self, status: i32) {
+        let callbacks = {
+            let mut s = self.state();
+            if s.status.is_some() { return; }
+            s.status = Some(status);
+            mem::take(&mut s.callbacks)
+        };
+        self.0.1.notify_all();
+        for f in callbacks { f(status); }
+    }
+
+    fn complete(&self) { self.set_status(CL_COMPLETE); }
+}
+
+// Host-Zeiger für den Worker; Gültigkeit sichert der GpuEventGuard.
+// Quelle eines Uploads nur lesend (stammt aus `&[T]`), Ziel eines Downloads schreibend
+struct HostSrc(*const u8, usize);
+struct HostDst(*mut u8, usize);
+// SAFETY: the pointee outlives the command (see `Backend::write`/`read`).
+unsafe impl Send for HostSrc {}
+// SAFETY: as above; the caller does not access it meanwhile.
+unsafe impl Send for HostDst {}
+
+impl HostSrc {
+    // SAFETY: caller guarantees the pointer is still valid
+    unsafe fn slice<'a>(&self) -> &'a [u8] { unsafe { std::slice::from_raw_parts(self.0, self.1) } }
+}
+
+impl HostDst {
+    // SAFETY: caller guarantees the pointer is still valid and unaliased
+    unsafe fn slice<'a>(&self) -> &'a mut [u8] { unsafe { std::slice::from_raw_parts_mut(self.0, self.1) } }
+}
+
+// Byte-Offsets (Device, Host) jeder Zeile zweier Rects gleicher Region
+fn rect_rows<T>(dev_base: usize, dev: &Rect, host: &Rect) -> Vec<(usize, usize)> {
+    let size = mem::size_of::<T>();
+    dev.rows().zip(host.rows()).map(|(d, h)| (dev_base + d * size, h * size)).collect()
+}
+
+impl Backend for Mock {
+    type Context = MockContext;
+    type Queue = MockQueue;
+    type Mem<T: Pod> = MockMem<T>;
+    type Event = MockEvent;
+
+    // Flags prüft der Typ-State, der Mock ignoriert sie
+    fn alloc<T: Pod>(ctx: &MockContext, len: usize, _flags: cl_mem_flags) -> Result<MockMem<T>, ClError> {
+        let bytes = vec![0u8; len * mem::size_of::<T>()];
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let storage = Arc::new(MockStorage { bytes: Mutex::new(bytes), align: ctx.align, id });
+        Ok(MockMem { storage, offset: 0, id, staging: Box::default() })
+    }
+
+    // USE_HOST_PTR als Kopie simuliert (erlaubtes Caching laut Spezifikation),
+    // kein Aliasing: siehe Doku von `Mock`
+    unsafe fn alloc_use_host<T: Pod>(ctx: &MockContext, host: &mut [T], flags: cl_mem_flags) -> Result<MockMem<T>, ClError> {
+        let mem = Self::alloc::<T>(ctx, host.len(), flags)?;
+        mem.storage.bytes.lock().unwrap().copy_from_slice(bytemuck::cast_slice(host));
+        Ok(mem)
+    }
+
+    fn sub_buffer<T: Pod>(mem: &MockMem<T>, offset: usize, len: usize) -> Result<MockMem<T>, ClError> {
+        let origin = mem.offset + offset * mem::size_of::<T>();
+        if !origin.is_multiple_of(mem.storage.align) {
+            return Err(ClError::from(CL_MISALIGNED_SUB_BUFFER_OFFSET));
+        }
+        if len == 0 || origin + len * mem::size_of::<T>() > mem.storage.bytes.lock().unwrap().len() {
+            return Err(ClError::from(CL_INVALID_VALUE));
+        }
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        Ok(MockMem { storage: mem.storage.clone(), offset: origin, id, staging: Box::default() })
+    }
+
+    fn base_align<T: Pod>(mem: &MockMem<T>) -> Result<usize, ClError> { Ok(mem.storage.align) }
+
+    fn mem_id<T: Pod>(mem: &MockMem<T>) -> usize { mem.id }
+
+    fn parent_id<T: Pod>(mem: &MockMem<T>) -> Option<usize> { (mem.id != mem.storage.id).then_some(mem.storage.id) }
+
+    unsafe fn write<T: Pod>(
+        queue: &MockQueue,
+        mem: &mut MockMem<T>,
+        offset: usize,
+        src: &[T],
+        wait: &[&MockEvent],
+    ) -> Result<MockEvent, ClError> {
+        let src: &[u8] = bytemuck::cast_slice(src);
+        let host = HostSrc(src.as_ptr(), src.len());
+        let dev = mem.storage.clone();
+        let at = mem.offset + offset * mem::size_of::<T>();
+        Ok(queue.submit(wait, Box::new(move || {
+            // SAFETY: `src` outlives the command, guaranteed by the caller
+            let src = unsafe { host.slice() };
+            dev.bytes.lock().unwrap()[at..at + src.len()].copy_from_slice(src);
+        })))
+    }
+
+    unsafe fn read<T: Pod>(
+        queue: &MockQueue,
+        mem: &MockMem<T>,
+        offset: usize,
+        dst: &mut [T],
+        wait: &[&MockEvent],
+    ) -> Result<MockEvent, ClError> {
+        let dst: &mut [u8] = bytemuck::cast_slice_mut(dst);
+        let host = HostDst(dst.as_mut_ptr(), dst.len());
+        let dev = mem.storage.clone();
+        let at = mem.offset + offset * mem::size_of::<T>();
+        Ok(queue.submit(wait, Box::new(move || {
+            // SAFETY: `dst` outlives the command and is not accessed meanwhile
+            let dst = unsafe { host.slice() };
+            dst.copy_from_slice(&dev.bytes.lock().unwrap()[at..at + dst.len()]);
+        })))
+    }
+
+    fn copy<T: Pod>(
+        queue: &MockQueue,
+        src: &MockMem<T>,
+        src_offset: usize,
+        dst: &mut MockMem<T>,
+        dst_offset: usize,
+        count: usize,
+        wait: &[&MockEvent],
+    ) -> Result<MockEvent, ClError> {
+        let size = mem::size_of::<T>();
+        let (from, to, n) = (src.offset + src_offset * size, dst.offset + dst_offset * size, count * size);
+        let (src, dst) = (src.storage.clone(), dst.storage.clone());
+        Ok(queue.submit(wait, Box::new(move || {
+            // Sub-Buffer desselben Parents teilen sich den Speicher
+            if Arc::ptr_eq(&src, &dst) {
+                src.bytes.lock().unwrap().copy_within(from..from + n, to);
+            } else {
+                let src = src.bytes.lock().unwrap();
+                dst.bytes.lock().unwrap()[to..to + n].copy_from_slice(&src[from..from + n]);
+            }
+        })))
+    }
+
+    fn fill<T: Pod>(
+        queue: &MockQueue,
+        mem: &mut MockMem<T>,
+        value: T,
+        offset: usize,
+        count: usize,
+        wait: &[&MockEvent],
+    ) -> Result<MockEvent, ClError> {
+        let pattern = bytemuck::bytes_of(&value).to_vec();
+        let at = mem.offset + offset * pattern.len();
+        let dev = mem.storage.clone();
+        Ok(queue.submit(wait, Box::new(move || {
+            let mut dev = dev.bytes.lock().unwrap();
+            for chunk in dev[at..at + count * pattern.len()].chunks_exact_mut(pattern.len().max(1)) {
+                chunk.copy_from_slice(&pattern);
+            }
+        })))
+    }
+
+    unsafe fn write_rect<T: Pod>(
+        queue: &MockQueue,
+        mem: &mut MockMem<T>,
+        rect: &Rect,
+        src: &[T],
+        src_rect: &Rect,
+        wait: &[&MockEvent],
+    ) -> Result<MockEvent, ClError> {
+        let src: &[u8] = bytemuck::cast_slice(src);
+        let host = HostSrc(src.as_ptr(), src.len());
+        let (rows, n) = (rect_rows::<T>(mem.offset, rect, src_rect), rect.region()[0] * mem::size_of::<T>());
+        let dev = mem.storage.clone();
+        Ok(queue.submit(wait, Box::new(move || {
+            // SAFETY: `src` outlives the command, guaranteed by the caller
+            let src = unsafe { host.slice() };
+            let mut dev = dev.bytes.lock().unwrap();
+            for (d, h) in rows { dev[d..d + n].copy_from_slice(&src[h..h + n]); }
+        })))
+    }
+
+    unsafe fn read_rect<T: Pod>(
+        queue: &MockQueue,
+        mem: &MockMem<T>,
+        rect: &Rect,
+        dst: &mut [T],
+        dst_rect: &Rect,
+        wait: &[&MockEvent],
+    ) -> Result<MockEvent, ClError> {
+        let dst: &mut [u8] = bytemuck::cast_slice_mut(dst);
+        let host = HostDst(dst.as_mut_ptr(), dst.len());
+        let (rows, n) = (rect_rows::<T>(mem.offset, rect, dst_rect), rect.region()[0] * mem::size_of::<T>());
+        let dev = mem.storage.clone();
+        Ok(queue.submit(wait, Box::new(move || {
+            // SAFETY: `dst` outlives the command and is not accessed meanwhile
+            let dst = unsafe { host.slice() };
+            let dev = dev.bytes.lock().unwrap();
+            for (d, h) in rows { dst[h..h + n].copy_from_slice(&dev[d..d + n]); }
+        })))
+    }
+
+    fn copy_rect<T: Pod>(
+        queue: &MockQueue,
+        src: &MockMem<T>,
+        src_rect: &Rect,
+        dst: &mut MockMem<T>,
+        dst_rect: &Rect,
+        wait: &[&MockEvent],
+    ) -> Result<MockEvent, ClError> {
+        let size = mem::size_of::<T>();
+        let rows: Vec<_> = src_rect.rows().zip(dst_rect.rows())
+            .map(|(s, d)| (src.offset + s * size, dst.offset + d * size))
+            .collect();
+        let n = src_rect.region()[0] * size;
+        let (src, dst) = (src.storage.clone(), dst.storage.clone());
+        Ok(queue.submit(wait, Box::new(move || {
+            if Arc::ptr_eq(&src, &dst) {
+                let mut bytes = src.bytes.lock().unwrap();
+                for (s, d) in rows { bytes.copy_within(s..s + n, d); }
+            } else {
+                let (src, mut dst) = (src.bytes.lock().unwrap(), dst.bytes.lock().unwrap());
+                for (s, d) in rows { dst[d..d + n].copy_from_slice(&src[s..s + n]); }
+            }
+        })))
+    }
+
+    unsafe fn map<T: Pod>(queue: &MockQueue, mem: &mut MockMem<T>, len: usize, _write: bool) -> Result<*mut T, ClError> {
+        // blockierend wie CL_BLOCKING: vorher eingereihte Kommandos abwarten
+        queue.finish();
+        let bytes = mem.storage.bytes.lock().unwrap();
+        let at = mem.offset;
+        mem.staging = bytemuck::cast_slice(&bytes[at..at + len * mem::size_of::<T>()]).into();
+        Ok(mem.staging.as_mut_ptr())
+    }
+
+    unsafe fn unmap<T: Pod>(queue: &MockQueue, mem: &mut MockMem<T>, _ptr: *mut T, write: bool) -> Result<MockEvent, ClError> {
+        let staging = mem::take(&mut mem.staging);
+        // nur beim Write-Map zurückschreiben
+        let data: Vec<u8> = if write { bytemuck::cast_slice(&staging).to_vec() } else { Vec::new() };
+        let (dev, at) = (mem.storage.clone(), mem.offset);
+        Ok(queue.submit(&[], Box::new(move || {
+            dev.bytes.lock().unwrap()[at..at + data.len()].copy_from_slice(&data);
+        })))
+    }
+
+    fn retain(evt: &MockEvent) -> Result<MockEvent, ClError> { Ok(evt.clone()) }
+
+    fn wait(evt: &MockEvent) -> Result<(), ClError> {
+        let mut s = evt.state();
+        while s.status.is_none() {
+            s = evt.0.1.wait(s).unwrap_or_else(|e| e.into_inner());
+        }
+        match s.status {
+            Some(code) if code < 0 => Err(ClError::from(CL_EXEC_STATUS_ERROR_FOR_EVENTS_IN_WAIT_LIST)),
+            _ => Ok(()),
+        }
+    }
+
+    // alle abwarten, der erste Fehler wird gemeldet
+    fn wait_all(evts: &[&MockEvent]) -> Result<(), ClError> {
+        evts.iter().map(|evt| Self::wait(evt)).fold(Ok(()), Result::and)
+    }
+
+    fn status(evt: &MockEvent) -> Result<i32, ClError> {
+        Ok(evt.state().status.unwrap_or(CL_SUBMITTED))
+    }
+
+    fn on_complete(evt: &MockEvent, f: Callback) -> Result<(), ClError> {
+        let mut s = evt.state();
+        match s.status {
+            Some(status) => { drop(s); f(status); }
+            None         => s.callbacks.push(f),
+        }
+        Ok(())
+    }
+
+    fn keep_alive<T: Pod>(mem: &MockMem<T>, evt: &MockEvent) -> Result<(), ClError> {
+        let bytes = mem.storage.clone();
+        Self::on_complete(evt, Box::new(move |_| drop(bytes)))
+    }
+}
@@ -0,0 +1,187 @@
+//! src/backend/mod.rs
+//
+// Backend-Abstraktion unter GpuBuffer / GpuEventGuard / Tracern:
+// OpenCL als Default, Host-Speicher-Mock für Tests ohne GPU (auch unter Miri).
+
+use crate::{ClError, Rect};
+use bytemuck::Pod;
+use opencl3::types::cl_mem_flags;
+
+mod mock;
+mod opencl;
+
+pub use mock::{Mock, MockContext, MockEvent, MockMem, MockQueue};
+pub use opencl::OpenCl;
+
+/// Completion callback; receives the final execution status
+/// (`CL_COMPLETE` or a negative error code).
+pub type Callback = Box<dyn FnOnce(i32) + Send>;
+
+/// Device API the typestate wrapper runs on.
+///
+/// All enqueue operations are non-blocking, start only after every event
+/// in `wait` has completed and return the event of the command; validation
+/// of lengths and ranges happens in the wrapper.
+pub trait Backend: Sized + 'static {
+    type Context;
+    type Queue;
+    type Mem<T: Pod>;
+    type Event;
+
+    /// Allocates an uninitialised buffer of `len` elements with the given
+    /// `CL_MEM_*` flags (e.g. `CL_MEM_ALLOC_HOST_PTR` for pinned memory).
+    fn alloc<T: Pod>(ctx: &Self::Context, len: usize, flags: cl_mem_flags) -> Result<Self::Mem<T>, ClError>;
+
+    /// Allocates a buffer backed by `host` (`CL_MEM_USE_HOST_PTR` is added to `flags`).
+    ///
+    /// # Safety
+    /// `host` must stay alive and must not be accessed until the buffer is released.
+    unsafe fn alloc_use_host<T: Pod>(
+        ctx: &Self::Context,
+        host: &mut [T],
+        flags: cl_mem_flags,
+    ) -> Result<Self::Mem<T>, ClError>;
+
+    /// Sub-buffer over elements `offset..offset + len` of `mem`, sharing its storage.
+    fn sub_buffer<T: Pod>(mem: &Self::Mem<T>, offset: usize, len: usize) -> Result<Self::Mem<T>, ClError>;
+
+    /// Required alignment of sub-buffer origins in bytes
+    /// (`CL_DEVICE_MEM_BASE_ADDR_ALIGN`, maximum over the context's devices).
+    fn base_align<T: Pod>(mem: &Self::Mem<T>) -> Result<usize, ClError>;
+
+    /// Identity of the memory object, stable while it is alive.
+    fn mem_id<T: Pod>(mem: &Self::Mem<T>) -> usize;
+
+    /// [`mem_id`](Self::mem_id) of the buffer a sub-buffer was created from,
+    /// `None` for a buffer of its own.
+    fn parent_id<T: Pod>(mem: &Self::Mem<T>) -> Option<usize>;
+
+    /// Enqueues a copy of `src` to elements `offset..offset + src.len()`.
+    ///
+    /// # Safety
+    /// `src` must stay alive and unmodified until the returned event completes.
+    unsafe fn write<T: Pod>(
+        queue: &Self::Queue,
+        mem: &mut Self::Mem<T>,
+        offset: usize,
+        src: &[T],
+        wait: &[&Self::Event],
+    ) -> Result<Self::Event, ClError>;
+
+    /// Enqueues a copy of elements `offset..offset + dst.len()` into `dst`.
+    ///
+    /// # Safety
+    /// `dst` must stay alive and must not be accessed until the returned
+    /// event completes.
+    unsafe fn read<T: Pod>(
+        queue: &Self::Queue,
+        mem: &Self::Mem<T>,
+        offset: usize,
+        dst: &mut [T],
+        wait: &[&Self::Event],
+    ) -> Result<Self::Event, ClError>;
+
+    /// Enqueues a device-side copy of `count` elements.
+    fn copy<T: Pod>(
+        queue: &Self::Queue,
+        src: &Self::Mem<T>,
+        src_offset: usize,
+        dst: &mut Self::Mem<T>,
+        dst_offset: usize,
+        count: usize,
+        wait: &[&Self::Event],
+    ) -> Result<Self::Event, ClError>;
+
+    /// Enqueues filling `count` elements from `offset` with `value`.
+    fn fill<T: Pod>(
+        queue: &Self::Queue,
+        mem: &mut Self::Mem<T>,
+        value: T,
+        offset: usize,
+        count: usize,
+        wait: &[&Self::Event],
+    ) -> Result<Self::Event, ClError>;
+
+    /// Enqueues a copy of the box `src_rect` of `src` into the box `rect`.
+    ///
+    /// # Safety
+    /// As for [`write`](Self::write); both boxes are validated by the caller.
+    unsafe fn write_rect<T: Pod>(
+        queue: &Self::Queue,
+        mem: &mut Self::Mem<T>,
+        rect: &Rect,
+        src: &[T],
+        src_rect: &Rect,
+        wait: &[&Self::Event],
+    ) -> Result<Self::Event, ClError>;
+
+    /// Enqueues a copy of the box `rect` into the box `dst_rect` of `dst`.
+    ///
+    /// # Safety
+    /// As for [`read`](Self::read); both boxes are validated by the caller.
+    unsafe fn read_rect<T: Pod>(
+        queue: &Self::Queue,
+        mem: &Self::Mem<T>,
+        rect: &Rect,
+        dst: &mut [T],
+        dst_rect: &Rect,
+        wait: &[&Self::Event],
+    ) -> Result<Self::Event, ClError>;
+
+    /// Enqueues a device-side copy between two boxes of equal region.
+    fn copy_rect<T: Pod>(
+        queue: &Self::Queue,
+        src: &Self::Mem<T>,
+        src_rect: &Rect,
+        dst: &mut Self::Mem<T>,
+        dst_rect: &Rect,
+        wait: &[&Self::Event],
+    ) -> Result<Self::Event, ClError>;
+
+    /// Maps the first `len` elements into host memory, blocking until the
+    /// pointer is valid. `write` requests write access in addition to read.
+    ///
+    /// # Safety
+    /// `mem` must not be used by other commands until [`unmap`](Self::unmap).
+    unsafe fn map<T: Pod>(
+        queue: &Self::Queue,
+        mem: &mut Self::Mem<T>,
+        len: usize,
+        write: bool,
+    ) -> Result<*mut T, ClError>;
+
+    /// Enqueues unmapping `ptr`, obtained from [`map`](Self::map) with the same `write`.
+    ///
+    /// # Safety
+    /// `ptr` must not be accessed afterwards.
+    unsafe fn unmap<T: Pod>(
+        queue: &Self::Queue,
+        mem: &mut Self::Mem<T>,
+        ptr: *mut T,
+        write: bool,
+    ) -> Result<Self::Event, ClError>;
+
+    /// Second handle to the same command (`clRetainEvent`), e.g. to keep it
+    /// as a dependency independent of its guard.
+    fn retain(evt: &Self::Event) -> Result<Self::Event, ClError>;
+
+    /// Blocks until the command has finished.
+    fn wait(evt: &Self::Event) -> Result<(), ClError>;
+
+    /// Blocks until every command in `evts` has finished, with a single
+    /// call where the API allows (`clWaitForEvents`).
+    fn wait_all(evts: &[&Self::Event]) -> Result<(), ClError>;
+
+    /// Execution status: `CL_COMPLETE` (0), a positive pending state or a
+    /// negative error code.
+    fn status(evt: &Self::Event) -> Result<i32, ClError>;
+
+    /// Runs `f` once the command has finished. If the callback cannot be
+    /// registered, `f` runs immediately with the error code.
+    fn on_complete(evt: &Self::Event, f: Callback) -> Result<(), ClError>;
+
+    /// Keeps `mem` alive until the command has finished (an extra reference
+    /// released from a completion callback), so dropping its handle early
+    /// never frees memory the command still uses.
+    fn keep_alive<T: Pod>(mem: &Self::Mem<T>, evt: &Self::Event) -> Result<(), ClError>;
+}
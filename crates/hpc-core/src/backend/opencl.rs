@@ -0,0 +1,228 @@
+//! src/backend/opencl.rs
+//
+// Default-Backend: dünne Schicht über opencl3.
+
+use super::{Backend, Callback};
+use crate::{ClError, Rect};
+use bytemuck::Pod;
+use opencl3::{
+    command_queue::CommandQueue,
+    context::{context::{get_context_info, CL_CONTEXT_DEVICES}, Context},
+    device::Device,
+    event::{retain_event, wait_for_events, Event, CL_COMPLETE},
+    memory::{release_mem_object, retain_mem_object, Buffer, ClMem, CL_MAP_READ, CL_MAP_WRITE, CL_MEM_USE_HOST_PTR},
+    types::{cl_device_id, cl_event, cl_int, cl_mem, cl_mem_flags, CL_BLOCKING, CL_NON_BLOCKING},
+};
+use std::{ffi::c_void, mem, ptr};
+
+/// OpenCL via `opencl3`; the default backend of every wrapper type.
+pub struct OpenCl;
+
+// Rect in Elementen → OpenCL-Argumente (x-Werte und Pitches in Bytes),
+// Überlauf beim Umrechnen → InvalidRect
+fn cl_rect<T>(rect: &Rect) -> Result<([usize; 3], [usize; 3], usize, usize), ClError> {
+    let size = mem::size_of::<T>();
+    let ([ox, oy, oz], [rx, ry, rz]) = (rect.origin(), rect.region());
+    let bytes = |n: usize| n.checked_mul(size).ok_or(ClError::InvalidRect {
+        region: rect.region(),
+        row_pitch: rect.row_pitch(),
+        slice_pitch: rect.slice_pitch(),
+    });
+    Ok(([bytes(ox)?, oy, oz], [bytes(rx)?, ry, rz], bytes(rect.row_pitch())?, bytes(rect.slice_pitch())?))
+}
+
+// Wait-List als rohe cl_event-Handles (die Runtime hält eigene Referenzen)
+fn cl_wait(wait: &[&Event]) -> Vec<cl_event> {
+    wait.iter().map(|e| e.get()).collect()
+}
+
+// Trampolin für `on_complete`: user_data ist ein `Box<Callback>`
+extern "C" fn complete_trampoline(_evt: cl_event, status: cl_int, user_data: *mut c_void) {
+    // SAFETY: Pointer was obtained via `Box::into_raw` in `on_complete`;
+    // the runtime invokes the callback exactly once.
+    let f: Box<Callback> = unsafe { Box::from_raw(user_data.cast()) };
+    f(status);
+}
+
+impl Backend for OpenCl {
+    type Context = Context;
+    type Queue = CommandQueue;
+    type Mem<T: Pod> = Buffer<T>;
+    type Event = Event;
+
+    fn alloc<T: Pod>(ctx: &Context, len: usize, flags: cl_mem_flags) -> Result<Buffer<T>, ClError> {
+        Ok(Buffer::<T>::create(ctx, flags, len, ptr::null_mut())?)
+    }
+
+    unsafe fn alloc_use_host<T: Pod>(ctx: &Context, host: &mut [T], flags: cl_mem_flags) -> Result<Buffer<T>, ClError> {
+        let flags = flags | CL_MEM_USE_HOST_PTR;
+        Ok(Buffer::<T>::create(ctx, flags, host.len(), host.as_mut_ptr().cast())?)
+    }
+
+    fn sub_buffer<T: Pod>(mem: &Buffer<T>, offset: usize, len: usize) -> Result<Buffer<T>, ClError> {
+        // flags = 0: Zugriffsrechte des Parents erben
+        Ok(mem.create_sub_buffer(0, offset, len)?)
+    }
+
+    fn base_align<T: Pod>(mem: &Buffer<T>) -> Result<usize, ClError> {
+        let devices: Vec<isize> = get_context_info(mem.context()?, CL_CONTEXT_DEVICES)?.into();
+        let mut align = 1;
+        for id in devices {
+            // CL_DEVICE_MEM_BASE_ADDR_ALIGN ist in Bits angegeben
+            let bits = Device::new(id as cl_device_id).mem_base_addr_align()? as usize;
+            align = align.max(bits / 8);
+        }
+        Ok(align)
+    }
+
+    fn mem_id<T: Pod>(mem: &Buffer<T>) -> usize { mem.get() as usize }
+
+    fn parent_id<T: Pod>(mem: &Buffer<T>) -> Option<usize> {
+        // CL_MEM_ASSOCIATED_MEMOBJECT: NULL für eigenständige Buffer
+        mem.associated_memobject().ok().filter(|p| !p.is_null()).map(|p| p as usize)
+    }
+
+    unsafe fn write<T: Pod>(
+        queue: &CommandQueue,
+        mem: &mut Buffer<T>,
+        offset: usize,
+        src: &[T],
+        wait: &[&Event],
+    ) -> Result<Event, ClError> {
+        Ok(queue.enqueue_write_buffer(mem, CL_NON_BLOCKING, offset * mem::size_of::<T>(), src, &cl_wait(wait))?)
+    }
+
+    unsafe fn read<T: Pod>(
+        queue: &CommandQueue,
+        mem: &Buffer<T>,
+        offset: usize,
+        dst: &mut [T],
+        wait: &[&Event],
+    ) -> Result<Event, ClError> {
+        Ok(queue.enqueue_read_buffer(mem, CL_NON_BLOCKING, offset * mem::size_of::<T>(), dst, &cl_wait(wait))?)
+    }
+
+    fn copy<T: Pod>(
+        queue: &CommandQueue,
+        src: &Buffer<T>,
+        src_offset: usize,
+        dst: &mut Buffer<T>,
+        dst_offset: usize,
+        count: usize,
+        wait: &[&Event],
+    ) -> Result<Event, ClError> {
+        let size = mem::size_of::<T>();
+        Ok(queue.enqueue_copy_buffer(src, dst, src_offset * size, dst_offset * size, count * size, &cl_wait(wait))?)
+    }
+
+    fn fill<T: Pod>(
+        queue: &CommandQueue,
+        mem: &mut Buffer<T>,
+        value: T,
+        offset: usize,
+        count: usize,
+        wait: &[&Event],
+    ) -> Result<Event, ClError> {
+        // Pattern wird beim Enqueue kopiert, der Stack-Wert darf danach verschwinden
+        let size = mem::size_of::<T>();
+        Ok(queue.enqueue_fill_buffer(mem, &[value], offset * size, count * size, &cl_wait(wait))?)
+    }
+
+    unsafe fn write_rect<T: Pod>(
+        queue: &CommandQueue,
+        mem: &mut Buffer<T>,
+        rect: &Rect,
+        src: &[T],
+        src_rect: &Rect,
+        wait: &[&Event],
+    ) -> Result<Event, ClError> {
+        let (origin, region, row, slice) = cl_rect::<T>(rect)?;
+        let (host_origin, _, host_row, host_slice) = cl_rect::<T>(src_rect)?;
+        // Regionen sind geprüft, `src` lebt laut Vertrag bis zum Event
+        Ok(queue.enqueue_write_buffer_rect(
+            mem, CL_NON_BLOCKING, origin.as_ptr(), host_origin.as_ptr(), region.as_ptr(),
+            row, slice, host_row, host_slice, src.as_ptr() as *mut c_void, &cl_wait(wait),
+        )?)
+    }
+
+    unsafe fn read_rect<T: Pod>(
+        queue: &CommandQueue,
+        mem: &Buffer<T>,
+        rect: &Rect,
+        dst: &mut [T],
+        dst_rect: &Rect,
+        wait: &[&Event],
+    ) -> Result<Event, ClError> {
+        let (origin, region, row, slice) = cl_rect::<T>(rect)?;
+        let (host_origin, _, host_row, host_slice) = cl_rect::<T>(dst_rect)?;
+        // Regionen sind geprüft, `dst` lebt laut Vertrag bis zum Event
+        Ok(queue.enqueue_read_buffer_rect(
+            mem, CL_NON_BLOCKING, origin.as_ptr(), host_origin.as_ptr(), region.as_ptr(),
+            row, slice, host_row, host_slice, dst.as_mut_ptr().cast(), &cl_wait(wait),
+        )?)
+    }
+
+    fn copy_rect<T: Pod>(
+        queue: &CommandQueue,
+        src: &Buffer<T>,
+        src_rect: &Rect,
+        dst: &mut Buffer<T>,
+        dst_rect: &Rect,
+        wait: &[&Event],
+    ) -> Result<Event, ClError> {
+        let (src_origin, region, src_row, src_slice) = cl_rect::<T>(src_rect)?;
+        let (dst_origin, _, dst_row, dst_slice) = cl_rect::<T>(dst_rect)?;
+        Ok(queue.enqueue_copy_buffer_rect(
+            src, dst, src_origin.as_ptr(), dst_origin.as_ptr(), region.as_ptr(),
+            src_row, src_slice, dst_row, dst_slice, &cl_wait(wait),
+        )?)
+    }
+
+    unsafe fn map<T: Pod>(queue: &CommandQueue, mem: &mut Buffer<T>, len: usize, write: bool) -> Result<*mut T, ClError> {
+        let flags = if write { CL_MAP_READ | CL_MAP_WRITE } else { CL_MAP_READ };
+        let mut ptr: cl_mem = ptr::null_mut();
+        // blockierend: das Event ist bei Rückkehr bereits CL_COMPLETE
+        queue.enqueue_map_buffer(mem, CL_BLOCKING, flags, 0, len * mem::size_of::<T>(), &mut ptr, &[])?;
+        Ok(ptr.cast())
+    }
+
+    unsafe fn unmap<T: Pod>(queue: &CommandQueue, mem: &mut Buffer<T>, ptr: *mut T, _write: bool) -> Result<Event, ClError> {
+        Ok(queue.enqueue_unmap_mem_object(mem.get(), ptr.cast(), &[])?)
+    }
+
+    fn retain(evt: &Event) -> Result<Event, ClError> {
+        retain_event(evt.get())?;
+        // die zusätzliche Referenz gibt das neue Event im Drop frei
+        Ok(Event::new(evt.get()))
+    }
+
+    fn wait(evt: &Event) -> Result<(), ClError> { Ok(evt.wait()?) }
+
+    fn wait_all(evts: &[&Event]) -> Result<(), ClError> {
+        // leere Liste wäre CL_INVALID_VALUE
+        if evts.is_empty() { return Ok(()); }
+        Ok(wait_for_events(&cl_wait(evts))?)
+    }
+
+    fn status(evt: &Event) -> Result<i32, ClError> { Ok(evt.command_execution_status()?.0) }
+
+    fn on_complete(evt: &Event, f: Callback) -> Result<(), ClError> {
+        let ptr = Box::into_raw(Box::new(f)) as *mut c_void;
+        if let Err(e) = evt.set_callback(CL_COMPLETE, complete_trampoline, ptr) {
+            // SAFETY: registration failed, so the runtime did NOT take
+            // ownership of `ptr`; reclaim it and run the callback now.
+            let f: Box<Callback> = unsafe { Box::from_raw(ptr.cast()) };
+            f(e.0);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn keep_alive<T: Pod>(mem: &Buffer<T>, evt: &Event) -> Result<(), ClError> {
+        retain_mem_object(mem.get())?;
+        // cl_mem als usize, damit der Callback `Send` ist
+        let raw = mem.get() as usize;
+        // schlägt die Registrierung fehl, läuft der Callback sofort
+        Self::on_complete(evt, Box::new(move |_| { let _ = release_mem_object(raw as cl_mem); }))
+    }
+}
@@ -0,0 +1,172 @@
+//! src/copy.rs
+//
+// Device-interne Kommandos ohne Host-Speicher: Buffer-zu-Buffer-Kopie (D2D)
+// und Fill, z.B. Reset der Jacobi-Ping-Pong-Buffer ohne Umweg über den Host.
+
+use crate::{
+    keep_alive, Access, Backend, ClError, EnqueueError, Enqueued, GpuBuffer, GpuEventGuard, InFlight, OpenCl, Ready, ReadWrite,
+    Writable,
+};
+use bytemuck::Pod;
+use std::{mem, ops::Range};
+
+#[cfg(feature = "memtrace")]
+use crate::{trace_begin, trace_on_complete, Dir};
+
+#[cfg(feature = "metrics")]
+use crate::record;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Result of a device-to-device copy: source and destination in flight
+/// (an [`InFlightSet`](crate::InFlightSet)) plus the guard of the copy, or
+/// both buffers untouched together with the error.
+/// `'a` ends no later than the host memory of a [`UseHostPtr`](crate::UseHostPtr)
+/// buffer (`'static` otherwise).
+pub type Copied<'a, T, D, B = OpenCl, A = ReadWrite, DA = ReadWrite> = Result<
+    ((GpuBuffer<T, InFlight<'a>, B, A>, GpuBuffer<T, InFlight<'a>, B, DA>), GpuEventGuard<'a, B>),
+    EnqueueError<(GpuBuffer<T, Ready, B, A>, GpuBuffer<T, D, B, DA>)>,
+>;
+
+// Ready → Device (D2D)
+impl<T: Pod, B: Backend, A: Access> GpuBuffer<T, Ready, B, A> {
+
+    /// Copies the whole buffer into `dst`; both must have the same length.
+    pub fn enqueue_copy_to<'a, D: Writable, DA: Access + 'a>(
+        self,
+        queue: &B::Queue,
+        dst: GpuBuffer<T, D, B, DA>,
+    ) -> Copied<'a, T, D, B, A, DA>
+    where
+        A: 'a,
+    {
+        if dst.len != self.len {
+            let err = ClError::SizeMismatch { expected: self.len, actual: dst.len };
+            return Err(EnqueueError::new((self, dst), err));
+        }
+        let len = self.len;
+        self.copy_unchecked(queue, 0, dst, 0, len, &[])
+    }
+
+    /// Copies the elements in `src_range` to `dst_offset..` in `dst`.
+    pub fn enqueue_copy_range_to<'a, D: Writable, DA: Access + 'a>(
+        self,
+        queue: &B::Queue,
+        src_range: Range<usize>,
+        dst: GpuBuffer<T, D, B, DA>,
+        dst_offset: usize,
+    ) -> Copied<'a, T, D, B, A, DA>
+    where
+        A: 'a,
+    {
+        let checked = self.check_span(&src_range).and_then(|count| dst.check_range(dst_offset, count).map(|_| count));
+        let count = match checked {
+            Ok(count) => count,
+            Err(err)  => return Err(EnqueueError::new((self, dst), err)),
+        };
+        self.copy_unchecked(queue, src_range.start, dst, dst_offset, count, &[])
+    }
+
+    pub(crate) fn copy_unchecked<'a, D: Writable, DA: Access + 'a>(
+        self,
+        queue: &B::Queue,
+        src_offset: usize,
+        mut dst: GpuBuffer<T, D, B, DA>,
+        dst_offset: usize,
+        count: usize,
+        wait: &[&B::Event],
+    ) -> Copied<'a, T, D, B, A, DA>
+    where
+        A: 'a,
+    {
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let bytes = count * mem::size_of::<T>();
+
+        #[cfg(feature="memtrace")]
+        let token_box = trace_begin(Dir::D2D, bytes);
+
+        let evt = match B::copy(queue, &self.buf, src_offset, &mut dst.buf, dst_offset, count, wait) {
+            Ok(evt) => evt,
+            Err(e)  => return Err(EnqueueError::new((self, dst), e.context("enqueue_copy", Some(bytes)))),
+        };
+
+        #[cfg(feature="memtrace")]
+        trace_on_complete::<B>(&evt, token_box);
+
+        #[cfg(feature="metrics")]
+        record("enqueue_copy", t);
+
+        keep_alive::<T, B>(&self.buf, &evt);
+        keep_alive::<T, B>(&dst.buf, &evt);
+        let ids = [self.id, dst.id];
+        Ok((
+            (self.into_state(), dst.into_state()),
+            GpuEventGuard::new(evt, A::HOST_PTR || DA::HOST_PTR, &ids),
+        ))
+    }
+}
+
+// Fill: Zero-/Konstant-Initialisierung direkt auf dem Device
+impl<T: Pod, S: Writable, B: Backend, A: Access> GpuBuffer<T, S, B, A> {
+
+    /// Sets every element to `value`. With OpenCL, `size_of::<T>()` must be
+    /// a power of two up to 128 bytes (`clEnqueueFillBuffer` pattern size).
+    pub fn enqueue_fill<'a>(self, queue: &B::Queue, value: T) -> Enqueued<'a, T, S, B, A>
+    where
+        A: 'a,
+    {
+        let len = self.len;
+        self.fill_unchecked(queue, 0, len, value, &[])
+    }
+
+    /// Sets the elements in `range` to `value`.
+    pub fn enqueue_fill_range<'a>(self, queue: &B::Queue, range: Range<usize>, value: T) -> Enqueued<'a, T, S, B, A>
+    where
+        A: 'a,
+    {
+        let count = match self.check_span(&range) {
+            Ok(count) => count,
+            Err(err)  => return Err(EnqueueError::new(self, err)),
+        };
+        self.fill_unchecked(queue, range.start, count, value, &[])
+    }
+
+    pub(crate) fn fill_unchecked<'a>(
+        mut self,
+        queue: &B::Queue,
+        offset: usize,
+        count: usize,
+        value: T,
+        wait: &[&B::Event],
+    ) -> Enqueued<'a, T, S, B, A>
+    where
+        A: 'a,
+    {
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let bytes = count * mem::size_of::<T>();
+
+        #[cfg(feature="memtrace")]
+        let token_box = trace_begin(Dir::Fill, bytes);
+
+        let evt = match B::fill(queue, &mut self.buf, value, offset, count, wait) {
+            Ok(evt) => evt,
+            Err(e)  => return Err(EnqueueError::new(self, e.context("enqueue_fill", Some(bytes)))),
+        };
+
+        #[cfg(feature="memtrace")]
+        trace_on_complete::<B>(&evt, token_box);
+
+        #[cfg(feature="metrics")]
+        record("enqueue_fill", t);
+
+        keep_alive::<T, B>(&self.buf, &evt);
+        let ids = [self.id];
+        Ok((self.into_state(), GpuEventGuard::new(evt, A::HOST_PTR, &ids)))
+    }
+}
@@ -0,0 +1,239 @@
+//! src/error.rs
+//
+// Fehler‑Typen des Wrappers: OpenCL‑Codes mit symbolischem Namen,
+// Operation + Bytes als Kontext, Build‑Logs pro Device.
+
+use opencl3::error_codes::error_text;
+use std::{fmt, sync::atomic::{AtomicU8, Ordering}};
+
+/// Raw OpenCL status code, rendered as `CL_NAME (code): description`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClCode(pub i32);
+
+impl ClCode {
+    /// Symbolic name, e.g. `CL_OUT_OF_RESOURCES`.
+    pub fn name(self) -> &'static str { error_text(self.0) }
+
+    /// Short human description of the most common codes (empty if unknown).
+    pub fn description(self) -> &'static str {
+        match self.0 {
+            -1  => "no OpenCL device matches the requested type",
+            -2  => "device is currently not available",
+            -3  => "no OpenCL compiler available for the device",
+            -4  => "failed to allocate memory for a buffer object",
+            -5  => "failed to allocate resources on the device",
+            -6  => "failed to allocate resources on the host",
+            -7  => "profiling information is not available (queue without profiling?)",
+            -8  => "source and destination regions overlap",
+            -11 => "program failed to build (see build log)",
+            -12 => "failed to map the requested region into host memory",
+            -13 => "sub-buffer offset is not aligned to CL_DEVICE_MEM_BASE_ADDR_ALIGN",
+            -14 => "a command in the event wait list failed",
+            -30 => "invalid value passed to an API call",
+            -33 => "invalid device",
+            -34 => "invalid context",
+            -36 => "invalid command queue",
+            -38 => "invalid memory object",
+            -46 => "no kernel with this name in the program",
+            -48 => "invalid kernel object",
+            -49 => "kernel argument index out of range",
+            -50 => "invalid kernel argument value",
+            -51 => "kernel argument size does not match the kernel signature",
+            -52 => "not all kernel arguments have been set",
+            -53 => "work dimension must be 1, 2 or 3",
+            -54 => "local work size does not divide the global size or exceeds the device limit",
+            -55 => "local work size exceeds CL_DEVICE_MAX_WORK_ITEM_SIZES",
+            -56 => "invalid global work offset",
+            -57 => "invalid event wait list",
+            -58 => "invalid event object",
+            -59 => "operation not valid in the current state",
+            -61 => "buffer size is zero or exceeds CL_DEVICE_MAX_MEM_ALLOC_SIZE",
+            -63 => "invalid global work size",
+            -1001 => "no OpenCL platform found (ICD loader without installed driver?)",
+            _ => "",
+        }
+    }
+}
+
+impl fmt::Display for ClCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.0)?;
+        match self.description() {
+            ""   => Ok(()),
+            desc => write!(f, ": {desc}"),
+        }
+    }
+}
+
+/// Build log of one device, see [`ClError::Build`].
+#[derive(Clone, Debug)]
+pub struct BuildLog {
+    pub device: String,
+    pub log: String,
+}
+
+// Fehler‑Typ
+#[derive(thiserror::Error, Debug)]
+pub enum ClError {
+    /// A wrapped OpenCL call failed; `op` names the wrapper operation.
+    #[error("{}{code}", op_prefix(.op, .bytes))]
+    Api { code: ClCode, op: Option<&'static str>, bytes: Option<usize> },
+    #[error("host slice has {actual} elements, expected {expected}")]
+    SizeMismatch { expected: usize, actual: usize },
+    #[error("range {offset}..{offset}+{count} exceeds buffer of {len} elements")]
+    OutOfRange { offset: usize, count: usize, len: usize },
+    #[error("rect region {region:?} does not fit row pitch {row_pitch} / slice pitch {slice_pitch}")]
+    InvalidRect { region: [usize; 3], row_pitch: usize, slice_pitch: usize },
+    #[error("rect regions differ: {src:?} vs {dst:?}")]
+    RectMismatch { src: [usize; 3], dst: [usize; 3] },
+    #[error("sub-buffer origin at byte {offset} is not a multiple of CL_DEVICE_MEM_BASE_ADDR_ALIGN ({align} bytes)")]
+    Misaligned { offset: usize, align: usize },
+    #[error("cannot rejoin: expected {expected} sub-buffers, got {actual}")]
+    SubBufferCount { expected: usize, actual: usize },
+    #[error("cannot rejoin: sub-buffer {index} does not belong to this split or is out of order")]
+    SubBufferMismatch { index: usize },
+    /// A [`Paired`](crate::Paired) buffer was passed to a different [`StreamPair`](crate::StreamPair).
+    #[error("buffer is paired with a different StreamPair")]
+    PairMismatch,
+    /// An in-flight buffer was chained after a guard that does not belong
+    /// to a command on it.
+    #[error("guard does not belong to a command on this buffer")]
+    GuardMismatch,
+    /// Waiting for outstanding commands exceeded the configured timeout.
+    #[error("timed out waiting for outstanding commands")]
+    Timeout,
+    /// Arguments that do not fit the call, e.g. an empty range or buffers
+    /// of another length handed back by a callback.
+    #[error("invalid arguments: {reason}")]
+    InvalidArgs { reason: &'static str },
+    /// An enqueued command terminated with a negative execution status.
+    #[error("command failed: {code}")]
+    CommandFailed { code: ClCode },
+    /// Program build failed; carries the build log of every device.
+    #[error("program build failed: {code}{}", build_logs(.logs))]
+    Build { code: ClCode, logs: Vec<BuildLog> },
+}
+
+fn op_prefix(op: &Option<&'static str>, bytes: &Option<usize>) -> String {
+    match (op, bytes) {
+        (Some(op), Some(b)) => format!("{op} ({b} bytes): "),
+        (Some(op), None)    => format!("{op}: "),
+        (None, _)           => String::new(),
+    }
+}
+
+fn build_logs(logs: &[BuildLog]) -> String {
+    logs.iter()
+        .map(|l| format!("\n--- {} ---\n{}", l.device, l.log.trim_end()))
+        .collect()
+}
+
+impl ClError {
+    /// OpenCL status code, if the error came from the API.
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            ClError::Api { code, .. }
+            | ClError::CommandFailed { code }
+            | ClError::Build { code, .. } => Some(code.0),
+            _ => None,
+        }
+    }
+
+    // Operation + Bytes an einen API‑Fehler hängen (vorhandener Kontext bleibt)
+    pub(crate) fn context(mut self, name: &'static str, size: Option<usize>) -> Self {
+        if let ClError::Api { op, bytes, .. } = &mut self {
+            op.get_or_insert(name);
+            if bytes.is_none() { *bytes = size; }
+        }
+        self
+    }
+}
+
+#[allow(unused_macros)]
+macro_rules! cl_try {
+    ($expr:expr) => {
+        let err = unsafe { $expr };
+        if err != 0 {
+            return Err(crate::ClError::from(err));
+        }
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use cl_try;
+
+impl From<opencl3::error_codes::ClError> for ClError {
+    fn from(err: opencl3::error_codes::ClError) -> Self { ClError::from(err.0) }
+}
+impl From<i32> for ClError {
+    fn from(code: i32) -> Self { ClError::Api { code: ClCode(code), op: None, bytes: None } }
+}
+
+/// Failed enqueue: carries the OpenCL error together with the untouched
+/// input (buffer or kernel arguments), so it can be retried or reused.
+#[derive(thiserror::Error)]
+#[error("{error}")]
+pub struct EnqueueError<B> {
+    pub buffer: B,
+    #[source]
+    pub error: ClError,
+}
+
+impl<B> EnqueueError<B> {
+    pub(crate) fn new(buffer: B, error: impl Into<ClError>) -> Self {
+        Self { buffer, error: error.into() }
+    }
+
+    pub fn into_parts(self) -> (B, ClError) { (self.buffer, self.error) }
+}
+
+impl<B> fmt::Debug for EnqueueError<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnqueueError")
+            .field("buffer", &std::any::type_name::<B>())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+// `?` in Funktionen mit `ClError` gibt den Buffer frei
+impl<B> From<EnqueueError<B>> for ClError {
+    fn from(err: EnqueueError<B>) -> Self { err.error }
+}
+
+/// What a dropped [`GpuEventGuard`](crate::GpuEventGuard) does with a failed
+/// command it discovers; checked alternatives are `guard.wait()` and
+/// `into_ready_checked`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropErrorPolicy {
+    /// Print the error to stderr (default).
+    Log,
+    /// Panic, unless the thread is already unwinding.
+    Panic,
+    /// Discard the error silently.
+    Ignore,
+}
+
+// global, da Drop keinen Kontext hat
+static DROP_POLICY: AtomicU8 = AtomicU8::new(DropErrorPolicy::Log as u8);
+
+/// Sets the [`DropErrorPolicy`] for all guards.
+pub fn set_drop_error_policy(policy: DropErrorPolicy) {
+    DROP_POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+pub fn drop_error_policy() -> DropErrorPolicy {
+    match DROP_POLICY.load(Ordering::Relaxed) {
+        1 => DropErrorPolicy::Panic,
+        2 => DropErrorPolicy::Ignore,
+        _ => DropErrorPolicy::Log,
+    }
+}
+
+// Fehler aus einem Drop nach der eingestellten Policy melden
+pub(crate) fn report_drop_error(err: &ClError) {
+    match drop_error_policy() {
+        DropErrorPolicy::Log    => eprintln!("hpc_core: dropped guard of failed command: {err}"),
+        DropErrorPolicy::Panic if !std::thread::panicking() => panic!("dropped guard of failed command: {err}"),
+        DropErrorPolicy::Panic | DropErrorPolicy::Ignore => {}
+    }
+}
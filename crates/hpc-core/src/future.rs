@@ -0,0 +1,89 @@
+//! src/future.rs
+//
+// async/await für GpuEventGuard: der Waker wird – wie der memtrace-Token –
+// per `Backend::on_complete` am Event registriert, kein Thread blockiert.
+
+use crate::{Backend, ClCode, ClError, GpuEventGuard, OpenCl};
+use std::{
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+// Zustand, den sich Future und Callback teilen
+#[derive(Default)]
+struct Shared {
+    status: Option<i32>,
+    waker: Option<Waker>,
+}
+
+/// Resolves once the guarded command has finished and hands the guard back,
+/// so `inflight.into_ready(guard.await?)` does not block the executor.
+///
+/// Dropping the future before completion drops the guard, which waits for
+/// the event as usual.
+pub struct EventFuture<'a, B: Backend = OpenCl> {
+    guard: Option<GpuEventGuard<'a, B>>,
+    shared: Arc<Mutex<Shared>>,
+    registered: bool,
+}
+
+impl<'a, B: Backend> IntoFuture for GpuEventGuard<'a, B> {
+    type Output = Result<GpuEventGuard<'a, B>, ClError>;
+    type IntoFuture = EventFuture<'a, B>;
+
+    fn into_future(self) -> EventFuture<'a, B> {
+        EventFuture { guard: Some(self), shared: Arc::default(), registered: false }
+    }
+}
+
+// Keine strukturelle Pin-Projektion, der Guard wird nur bewegt
+impl<B: Backend> Unpin for EventFuture<'_, B> {}
+
+impl<'a, B: Backend> Future for EventFuture<'a, B> {
+    type Output = Result<GpuEventGuard<'a, B>, ClError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let guard = this.guard.as_ref().expect("EventFuture polled after completion");
+
+        if !this.registered {
+            let shared = this.shared.clone();
+            let wake = move |status| {
+                let waker = {
+                    let mut s = shared.lock().unwrap_or_else(|e| e.into_inner());
+                    s.status = Some(status);
+                    s.waker.take()
+                };
+                if let Some(w) = waker { w.wake(); }
+            };
+            if let Err(e) = B::on_complete(&guard.evt, Box::new(wake)) {
+                this.guard = None;
+                return Poll::Ready(Err(e.context("EventFuture", None)));
+            }
+            this.registered = true;
+        }
+
+        let mut s = this.shared.lock().unwrap_or_else(|e| e.into_inner());
+        match s.status {
+            Some(code) if code < 0 => {
+                drop(s);
+                // Fehler geht an den Aufrufer, nicht an die Drop-Policy
+                if let Some(g) = this.guard.take() { g.dismiss(); }
+                Poll::Ready(Err(ClError::CommandFailed { code: ClCode(code) }))
+            }
+            Some(_) => {
+                drop(s);
+                Poll::Ready(Ok(this.guard.take().unwrap()))
+            }
+            None => {
+                match &mut s.waker {
+                    Some(w) => w.clone_from(cx.waker()),
+                    None    => s.waker = Some(cx.waker().clone()),
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! src/guard_set.rs
+//
+// Viele Transfers, ein Warten: Guards sammeln und alle Events mit einem
+// clWaitForEvents abwarten statt pro Guard einzeln (Sub-Buffer-Chunks,
+// Bandbreiten-Benchmarks).
+
+use crate::{report_drop_error, sealed::Sealed, Backend, ClError, GpuEventGuard, InFlightSet, OpenCl};
+
+/// In-flight buffers (or sets) together with their guards, completed by a
+/// single wait on all events.
+///
+/// `push` takes the result of an enqueue directly:
+/// `set.push(scope.write_at(part, queue, 0, chunk)?)`.
+pub struct GuardSet<'a, S, B: Backend = OpenCl> {
+    sets: Vec<S>,
+    guards: Vec<GpuEventGuard<'a, B>>,
+}
+
+impl<'a, S: InFlightSet<'a, B>, B: Backend> GuardSet<'a, S, B> {
+    pub fn new() -> Self { Self { sets: Vec::new(), guards: Vec::new() } }
+
+    pub fn with_capacity(n: usize) -> Self {
+        Self { sets: Vec::with_capacity(n), guards: Vec::with_capacity(n) }
+    }
+
+    pub fn push(&mut self, (set, guard): (S, GpuEventGuard<'a, B>)) {
+        self.sets.push(set);
+        self.guards.push(guard);
+    }
+
+    pub fn len(&self) -> usize { self.sets.len() }
+
+    pub fn is_empty(&self) -> bool { self.sets.is_empty() }
+
+    /// Waits for all commands at once and returns the Ready buffers in push
+    /// order. A failed command is handled by the
+    /// [`DropErrorPolicy`](crate::DropErrorPolicy).
+    pub fn join(self) -> Vec<S::Ready> {
+        if let Err(e) = wait_all(self.guards) {
+            report_drop_error(&e);
+        }
+        self.sets.into_iter().map(InFlightSet::relabel).collect()
+    }
+
+    /// Like [`join`](Self::join), but fails with [`ClError::CommandFailed`]
+    /// if any command terminated abnormally.
+    pub fn join_checked(self) -> Result<Vec<S::Ready>, ClError> {
+        wait_all(self.guards)?;
+        Ok(self.sets.into_iter().map(InFlightSet::relabel).collect())
+    }
+}
+
+impl<'a, S: InFlightSet<'a, B>, B: Backend> Default for GuardSet<'a, S, B> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<'a, S: InFlightSet<'a, B>, B: Backend> Extend<(S, GpuEventGuard<'a, B>)> for GuardSet<'a, S, B> {
+    fn extend<I: IntoIterator<Item = (S, GpuEventGuard<'a, B>)>>(&mut self, iter: I) {
+        for pair in iter { self.push(pair); }
+    }
+}
+
+impl<'a, S: InFlightSet<'a, B>, B: Backend> FromIterator<(S, GpuEventGuard<'a, B>)> for GuardSet<'a, S, B> {
+    fn from_iter<I: IntoIterator<Item = (S, GpuEventGuard<'a, B>)>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Heterogeneous `(in-flight, guard)` pairs for [`join`]: a tuple of up to
+/// six results of enqueue calls, e.g. buffers of different element types.
+pub trait Join<'a, B: Backend = OpenCl>: Sealed {
+    type Sets: InFlightSet<'a, B>;
+
+    #[doc(hidden)]
+    fn unzip(self) -> (Self::Sets, Vec<GpuEventGuard<'a, B>>);
+}
+
+/// Result of [`join`]: the Ready counterparts of the pairs' buffers.
+pub type Joined<'a, J, B = OpenCl> = <<J as Join<'a, B>>::Sets as InFlightSet<'a, B>>::Ready;
+
+/// Waits for all pairs of `pairs` with a single wait, see [`GuardSet::join`].
+pub fn join<'a, B: Backend, J: Join<'a, B>>(pairs: J) -> Joined<'a, J, B> {
+    let (sets, guards) = pairs.unzip();
+    if let Err(e) = wait_all(guards) {
+        report_drop_error(&e);
+    }
+    sets.relabel()
+}
+
+/// Like [`join`], but fails if any command terminated abnormally.
+pub fn join_checked<'a, B: Backend, J: Join<'a, B>>(pairs: J) -> Result<Joined<'a, J, B>, ClError> {
+    let (sets, guards) = pairs.unzip();
+    wait_all(guards)?;
+    Ok(sets.relabel())
+}
+
+// Ein Warten für alle statt eines pro Guard
+fn wait_all<B: Backend>(guards: Vec<GpuEventGuard<'_, B>>) -> Result<(), ClError> {
+    let evts: Vec<&B::Event> = guards.iter().map(|g| &g.evt).collect();
+    match B::wait_all(&evts) {
+        Ok(()) => {
+            guards.into_iter().for_each(GpuEventGuard::settle);
+            Ok(())
+        }
+        // Fehlschlag: alle einzeln abwarten, das erste fehlgeschlagene Kommando
+        // melden; sind alle einzeln erfolgreich, war nur das Sammel-Warten betroffen
+        Err(_) => {
+            let mut first = None;
+            for guard in guards {
+                if let Err(e) = guard.wait() && first.is_none() {
+                    first = Some(e);
+                }
+            }
+            first.map_or(Ok(()), Err)
+        }
+    }
+}
+
+impl<B: Backend> Sealed for GpuEventGuard<'_, B> {}
+
+macro_rules! join_impls {
+    ($($name:ident),+) => {
+        impl<'a, Bk: Backend, $($name: InFlightSet<'a, Bk>),+> Join<'a, Bk> for ($(($name, GpuEventGuard<'a, Bk>),)+) {
+            type Sets = ($($name,)+);
+
+            #[allow(non_snake_case)]
+            fn unzip(self) -> (Self::Sets, Vec<GpuEventGuard<'a, Bk>>) {
+                let ($($name,)+) = self;
+                let guards = vec![$($name.1),+];
+                (($($name.0,)+), guards)
+            }
+        }
+    };
+}
+
+join_impls!(A);
+join_impls!(A, B);
+join_impls!(A, B, C);
+join_impls!(A, B, C, D);
+join_impls!(A, B, C, D, E);
+join_impls!(A, B, C, D, E, F);
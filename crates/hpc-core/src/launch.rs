@@ -0,0 +1,408 @@
+//! src/launch.rs
+//
+// Kernel-Launch über den Typ-State: Buffer gehen Ready → InFlight,
+// der Kernel-Event steckt im zurückgegebenen GpuEventGuard.
+
+use crate::{
+    sealed::Sealed, Access, Backend, ClError, EnqueueError, GpuBuffer, GpuEventGuard, InFlight, KernelRead,
+    KernelWrite, MemKey, OpenCl, PairReadable, PairWritable, Paired, Ready, ReadWrite, Writable, keep_alive, wait_list,
+};
+use bytemuck::Pod;
+use crate::{BuildLog, ClCode};
+use opencl3::{
+    command_queue::CommandQueue, context::Context, device::Device, event::Event, kernel::Kernel,
+    memory::ClMem, program::Program, types::cl_uint,
+};
+use std::ptr;
+
+#[cfg(feature = "memtrace")]
+use crate::{trace_begin, trace_on_complete, Dir};
+
+#[cfg(feature = "metrics")]
+use crate::record;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Global/local work size and offset of a kernel launch (1–3 dimensions).
+#[derive(Clone, Copy, Debug)]
+pub struct NdRange {
+    dims: cl_uint,
+    global: [usize; 3],
+    local: Option<[usize; 3]>,
+    offset: Option<[usize; 3]>,
+}
+
+impl NdRange {
+    pub fn d1(x: usize) -> Self { Self::with_dims(1, [x, 1, 1]) }
+
+    pub fn d2(x: usize, y: usize) -> Self { Self::with_dims(2, [x, y, 1]) }
+
+    pub fn d3(x: usize, y: usize, z: usize) -> Self { Self::with_dims(3, [x, y, z]) }
+
+    fn with_dims(dims: cl_uint, global: [usize; 3]) -> Self {
+        Self { dims, global, local: None, offset: None }
+    }
+
+    /// Explicit work-group size; unused dimensions are ignored.
+    pub fn local(mut self, local: [usize; 3]) -> Self {
+        self.local = Some(local);
+        self
+    }
+
+    /// Global work offset; unused dimensions are ignored.
+    pub fn offset(mut self, offset: [usize; 3]) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn dims(&self) -> cl_uint { self.dims }
+
+    pub fn global(&self) -> [usize; 3] { self.global }
+}
+
+/// Buffer the kernel only reads, bound to kernel argument `.0`.
+/// [`Paired`] buffers are accepted by [`StreamPair::launch`](crate::StreamPair::launch) only.
+pub struct Input<T: Pod, A: KernelRead = ReadWrite, S: PairReadable = Ready>(pub cl_uint, pub GpuBuffer<T, S, OpenCl, A>);
+
+/// Buffer the kernel writes (or reads and writes), bound to kernel argument `.0`.
+/// [`Paired`] buffers are accepted by [`StreamPair::launch`](crate::StreamPair::launch) only.
+pub struct Output<T: Pod, S: PairWritable = Ready, A: KernelWrite = ReadWrite>(pub cl_uint, pub GpuBuffer<T, S, OpenCl, A>);
+
+/// [`Input`] whose buffer is still in flight: the kernel starts after `.2`,
+/// the guard of the pending command (e.g. the upload), without blocking.
+/// A guard of a command on another buffer fails the launch with
+/// [`ClError::GuardMismatch`].
+pub struct InputAfter<'g, T: Pod, A: KernelRead = ReadWrite>(
+    pub cl_uint,
+    pub GpuBuffer<T, InFlight<'g>, OpenCl, A>,
+    pub &'g GpuEventGuard<'g>,
+);
+
+/// [`Output`] whose buffer is still in flight, ordered after `.2`.
+pub struct OutputAfter<'g, T: Pod, A: KernelWrite = ReadWrite>(
+    pub cl_uint,
+    pub GpuBuffer<T, InFlight<'g>, OpenCl, A>,
+    pub &'g GpuEventGuard<'g>,
+);
+
+/// Buffer arguments of a launch: a single [`Input`]/[`Output`] (or their
+/// in-flight variants) or a tuple of them.
+///
+/// The in-flight set lives no longer than the arguments, so a kernel on a
+/// [`UseHostPtr`](crate::UseHostPtr) buffer keeps its host memory borrowed.
+pub trait KernelArgs: Sealed {
+    type InFlight<'a>: InFlightSet<'a> where Self: 'a;
+
+    // ein Argument ist UseHostPtr → Guard-Drop wartet
+    #[doc(hidden)]
+    const HOST_PTR: bool;
+
+    #[doc(hidden)]
+    fn bind(&self, kernel: &Kernel) -> Result<(), ClError>;
+
+    #[doc(hidden)]
+    fn into_in_flight<'a>(self) -> Self::InFlight<'a> where Self: 'a;
+
+    #[doc(hidden)]
+    fn mem_ids(&self, ids: &mut Vec<MemKey>);
+
+    #[doc(hidden)]
+    fn keep_alive(&self, evt: &Event);
+
+    // Events, auf die der Kernel zusätzlich warten muss (*After-Argumente);
+    // ein Guard, der nicht zum Buffer gehört, ist ein Fehler
+    #[doc(hidden)]
+    fn deps<'e>(&'e self, _wait: &mut Vec<&'e Event>) -> Result<(), ClError> { Ok(()) }
+}
+
+/// Arguments without [`Paired`] buffers, the ones [`launch`] accepts outside
+/// a [`StreamPair`](crate::StreamPair).
+///
+/// ```compile_fail
+/// # use hpc_core::{launch, GpuBuffer, Input, NdRange, Paired};
+/// # fn f(q: &opencl3::command_queue::CommandQueue, k: &opencl3::kernel::Kernel, buf: GpuBuffer<f32, Paired>) {
+/// let _ = launch(q, k, &NdRange::d1(4), Input(0, buf)); // error: `buf` belongs to a StreamPair
+/// # }
+/// ```
+pub trait Unpaired: KernelArgs {}
+
+/// One or more in-flight buffers completed by a single event.
+pub trait InFlightSet<'a, B: Backend = OpenCl>: Sealed + Sized {
+    type Ready;
+
+    // Ergebnis innerhalb eines StreamPair
+    #[doc(hidden)]
+    type Paired;
+
+    #[doc(hidden)]
+    fn relabel(self) -> Self::Ready;
+
+    #[doc(hidden)]
+    fn pair(self) -> Self::Paired;
+
+    /// Waits for `guard` once and turns every buffer of the set Ready.
+    fn into_ready(self, guard: GpuEventGuard<'a, B>) -> Self::Ready {
+        guard.finish();
+        self.relabel()
+    }
+
+    /// Like [`into_ready`](Self::into_ready), but fails with
+    /// [`ClError::CommandFailed`] if the command terminated abnormally.
+    fn into_ready_checked(self, guard: GpuEventGuard<'a, B>) -> Result<Self::Ready, ClError> {
+        guard.wait()?;
+        Ok(self.relabel())
+    }
+}
+
+impl<T: Pod, A: KernelRead, S: PairReadable> Sealed for Input<T, A, S> {}
+impl<T: Pod, A: KernelRead, S: PairReadable> KernelArgs for Input<T, A, S> {
+    type InFlight<'a> = GpuBuffer<T, InFlight<'a>, OpenCl, A> where Self: 'a;
+
+    const HOST_PTR: bool = A::HOST_PTR;
+
+    fn bind(&self, kernel: &Kernel) -> Result<(), ClError> {
+        kernel.set_arg(self.0, &self.1.buf.get())?;
+        Ok(())
+    }
+
+    fn into_in_flight<'a>(self) -> Self::InFlight<'a> where Self: 'a { self.1.into_state() }
+
+    fn mem_ids(&self, ids: &mut Vec<MemKey>) { ids.push(self.1.mem_key()); }
+
+    fn keep_alive(&self, evt: &Event) { keep_alive::<T, OpenCl>(&self.1.buf, evt); }
+}
+
+macro_rules! after_impls {
+    ($($name:ident: $cap:ident;)+) => {$(
+        impl<T: Pod, A: $cap> Sealed for $name<'_, T, A> {}
+        impl<'g, T: Pod, A: $cap> KernelArgs for $name<'g, T, A> {
+            type InFlight<'a> = GpuBuffer<T, InFlight<'a>, OpenCl, A> where Self: 'a;
+
+            const HOST_PTR: bool = A::HOST_PTR;
+
+            fn bind(&self, kernel: &Kernel) -> Result<(), ClError> {
+                kernel.set_arg(self.0, &self.1.buf.get())?;
+                Ok(())
+            }
+
+            fn into_in_flight<'a>(self) -> Self::InFlight<'a> where Self: 'a { self.1.into_state() }
+
+            fn mem_ids(&self, ids: &mut Vec<MemKey>) { ids.push(self.1.mem_key()); }
+
+            fn keep_alive(&self, evt: &Event) { keep_alive::<T, OpenCl>(&self.1.buf, evt); }
+
+            fn deps<'e>(&'e self, wait: &mut Vec<&'e Event>) -> Result<(), ClError> {
+                self.2.covers(self.1.id)?;
+                wait.push(&self.2.evt);
+                Ok(())
+            }
+        }
+    )+};
+}
+
+after_impls! {
+    InputAfter: KernelRead;
+    OutputAfter: KernelWrite;
+}
+
+impl<T: Pod, A: KernelRead> Unpaired for Input<T, A> {}
+impl<T: Pod, S: Writable, A: KernelWrite> Unpaired for Output<T, S, A> {}
+impl<T: Pod, A: KernelRead> Unpaired for InputAfter<'_, T, A> {}
+impl<T: Pod, A: KernelWrite> Unpaired for OutputAfter<'_, T, A> {}
+
+impl<T: Pod, S: PairWritable, A: KernelWrite> Sealed for Output<T, S, A> {}
+impl<T: Pod, S: PairWritable, A: KernelWrite> KernelArgs for Output<T, S, A> {
+    type InFlight<'a> = GpuBuffer<T, InFlight<'a>, OpenCl, A> where Self: 'a;
+
+    const HOST_PTR: bool = A::HOST_PTR;
+
+    fn bind(&self, kernel: &Kernel) -> Result<(), ClError> {
+        kernel.set_arg(self.0, &self.1.buf.get())?;
+        Ok(())
+    }
+
+    fn into_in_flight<'a>(self) -> Self::InFlight<'a> where Self: 'a { self.1.into_state() }
+
+    fn mem_ids(&self, ids: &mut Vec<MemKey>) { ids.push(self.1.mem_key()); }
+
+    fn keep_alive(&self, evt: &Event) { keep_alive::<T, OpenCl>(&self.1.buf, evt); }
+}
+
+impl<T: Pod, S, B: Backend, A: Access> Sealed for GpuBuffer<T, S, B, A> {}
+impl<'a, T: Pod, B: Backend, A: Access> InFlightSet<'a, B> for GpuBuffer<T, InFlight<'a>, B, A> {
+    type Ready = GpuBuffer<T, Ready, B, A>;
+    type Paired = GpuBuffer<T, Paired, B, A>;
+
+    fn relabel(self) -> Self::Ready { self.into_state() }
+
+    fn pair(self) -> Self::Paired { self.into_state() }
+}
+
+macro_rules! tuple_impls {
+    ($($name:ident),+) => {
+        impl<$($name: Sealed),+> Sealed for ($($name,)+) {}
+
+        impl<$($name: KernelArgs),+> KernelArgs for ($($name,)+) {
+            type InFlight<'a> = ($($name::InFlight<'a>,)+) where Self: 'a;
+
+            const HOST_PTR: bool = $($name::HOST_PTR)||+;
+
+            #[allow(non_snake_case)]
+            fn bind(&self, kernel: &Kernel) -> Result<(), ClError> {
+                let ($($name,)+) = self;
+                $($name.bind(kernel)?;)+
+                Ok(())
+            }
+
+            #[allow(non_snake_case)]
+            fn into_in_flight<'a>(self) -> Self::InFlight<'a> where Self: 'a {
+                let ($($name,)+) = self;
+                ($($name.into_in_flight(),)+)
+            }
+
+            #[allow(non_snake_case)]
+            fn mem_ids(&self, ids: &mut Vec<MemKey>) {
+                let ($($name,)+) = self;
+                $($name.mem_ids(ids);)+
+            }
+
+            #[allow(non_snake_case)]
+            fn keep_alive(&self, evt: &Event) {
+                let ($($name,)+) = self;
+                $($name.keep_alive(evt);)+
+            }
+
+            #[allow(non_snake_case)]
+            fn deps<'e>(&'e self, wait: &mut Vec<&'e Event>) -> Result<(), ClError> {
+                let ($($name,)+) = self;
+                $($name.deps(wait)?;)+
+                Ok(())
+            }
+        }
+
+        impl<$($name: Unpaired),+> Unpaired for ($($name,)+) {}
+
+        impl<'a, Bk: Backend, $($name: InFlightSet<'a, Bk>),+> InFlightSet<'a, Bk> for ($($name,)+) {
+            type Ready = ($($name::Ready,)+);
+            type Paired = ($($name::Paired,)+);
+
+            #[allow(non_snake_case)]
+            fn relabel(self) -> Self::Ready {
+                let ($($name,)+) = self;
+                ($($name.relabel(),)+)
+            }
+
+            #[allow(non_snake_case)]
+            fn pair(self) -> Self::Paired {
+                let ($($name,)+) = self;
+                ($($name.pair(),)+)
+            }
+        }
+    };
+}
+
+tuple_impls!(A);
+tuple_impls!(A, B);
+tuple_impls!(A, B, C);
+tuple_impls!(A, B, C, D);
+tuple_impls!(A, B, C, D, E);
+tuple_impls!(A, B, C, D, E, F);
+
+/// Result of [`launch`]: the buffer arguments in flight plus the kernel's
+/// guard, or the untouched arguments together with the error.
+pub type Launched<'a, A> = Result<(<A as KernelArgs>::InFlight<'a>, GpuEventGuard<'a>), EnqueueError<A>>;
+
+/// Binds the buffer arguments, enqueues `kernel` over `range` and returns
+/// the buffers in flight together with the kernel's event guard.
+///
+/// Scalar arguments are set beforehand with `kernel.set_arg`. On failure
+/// the arguments are handed back unchanged inside the [`EnqueueError`].
+pub fn launch<'a, A: Unpaired + 'a>(
+    queue: &CommandQueue,
+    kernel: &Kernel,
+    range: &NdRange,
+    args: A,
+) -> Launched<'a, A> {
+    launch_after(queue, kernel, range, args, &[])
+}
+
+/// Like [`launch`], but the kernel starts only after the commands of `deps`
+/// (e.g. uploads on a transfer queue) have completed. The guards stay with
+/// the caller.
+pub fn launch_after<'a, A: Unpaired + 'a>(
+    queue: &CommandQueue,
+    kernel: &Kernel,
+    range: &NdRange,
+    args: A,
+    deps: &[&GpuEventGuard<'_>],
+) -> Launched<'a, A> {
+    launch_unchecked(queue, kernel, range, args, &wait_list(deps))
+}
+
+pub(crate) fn launch_unchecked<'a, A: KernelArgs + 'a>(
+    queue: &CommandQueue,
+    kernel: &Kernel,
+    range: &NdRange,
+    args: A,
+    wait: &[&Event],
+) -> Launched<'a, A> {
+
+    #[cfg(feature="metrics")]
+    let t = Instant::now();
+
+    if let Err(e) = args.bind(kernel) {
+        return Err(EnqueueError::new(args, e.context("launch", None)));
+    }
+
+    // Wait-List vor dem Verschieben von `args` in rohe Handles auflösen
+    let mut events = wait.to_vec();
+    if let Err(e) = args.deps(&mut events) {
+        return Err(EnqueueError::new(args, e));
+    }
+    let wait: Vec<_> = events.iter().map(|e| e.get()).collect();
+
+    #[cfg(feature="memtrace")]
+    let token_box = trace_begin(Dir::Kernel, 0);
+
+    let evt = match queue.enqueue_nd_range_kernel(
+        kernel.get(),
+        range.dims,
+        range.offset.as_ref().map_or(ptr::null(), |o| o.as_ptr()),
+        range.global.as_ptr(),
+        range.local.as_ref().map_or(ptr::null(), |l| l.as_ptr()),
+        &wait,
+    ) {
+        Ok(evt) => evt,
+        Err(e)  => return Err(EnqueueError::new(args, ClError::from(e).context("launch", None))),
+    };
+
+    #[cfg(feature="memtrace")]
+    trace_on_complete::<crate::OpenCl>(&evt, token_box);
+
+    #[cfg(feature="metrics")]
+    record("launch", t);
+
+    args.keep_alive(&evt);
+    let mut keys = Vec::new();
+    args.mem_ids(&mut keys);
+    let ids: Vec<u64> = keys.iter().map(|key| key.id).collect();
+    Ok((args.into_in_flight(), GpuEventGuard::new(evt, A::HOST_PTR, &ids)))
+}
+
+/// Builds `src` for all devices of `ctx`. A failed build is reported as
+/// [`ClError::Build`] with the build log of every device.
+pub fn build_program(ctx: &Context, src: &str, options: &str) -> Result<Program, ClError> {
+    let mut program = Program::create_from_source(ctx, src)
+        .map_err(|e| ClError::from(e).context("build_program", None))?;
+
+    match program.build(ctx.devices(), options) {
+        Ok(()) => Ok(program),
+        Err(e) => {
+            let logs = ctx.devices().iter().map(|&id| BuildLog {
+                device: Device::new(id).name().unwrap_or_else(|_| format!("{id:?}")),
+                log:    program.get_build_log(id).unwrap_or_default(),
+            }).collect();
+            Err(ClError::Build { code: ClCode(e.0), logs })
+        }
+    }
+}
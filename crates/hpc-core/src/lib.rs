@@ -1,3 +1,7 @@
+mod error;
+pub use error::{ClError, ClCode, BuildLog, EnqueueError, DropErrorPolicy, set_drop_error_policy, drop_error_policy};
+use error::report_drop_error;
+
 // Feature‑Module
 #[cfg(feature = "metrics")]
 mod metrics;
@@ -9,6 +13,60 @@ mod memtracer;
 #[cfg(feature = "memtrace")]
 pub use memtracer::{start, Dir, CopyToken, flush_csv, TracingScope, is_auto_trace_enabled, enable_auto_trace, disable_auto_trace};
 
+mod launch;
+pub use launch::{
+    launch, launch_after, build_program, NdRange, Input, InputAfter, Output, OutputAfter, KernelArgs, Unpaired, InFlightSet,
+    Launched,
+};
+
+mod future;
+pub use future::EventFuture;
+
+mod access;
+pub use access::{
+    Access, DeviceAlloc, HostNoAccess, HostRead, HostReadOnly, HostWrite, HostWriteOnly, KernelAccess, KernelRead,
+    KernelWrite, ReadOnly, ReadWrite, UseHostPtr, WriteOnly,
+};
+
+mod backend;
+pub use backend::{Backend, Callback, OpenCl, Mock, MockContext, MockEvent, MockMem, MockQueue};
+
+mod runtime;
+pub use runtime::{GpuContext, GpuContextBuilder, DEVICE_ENV, PLATFORM_ENV};
+
+mod split;
+pub use split::{SplitBuffer, Parts, SplitResult, JoinResult};
+
+mod copy;
+pub use copy::Copied;
+
+mod rect;
+pub use rect::Rect;
+
+mod map;
+pub use map::{MapRead, MapWrite, Unmapped};
+
+mod pinned;
+pub use pinned::PinnedHostBuffer;
+
+mod stream;
+pub use stream::{StreamPair, Streamed, StreamCopied, StreamLaunched, Released};
+
+mod pipeline;
+pub use pipeline::{Pipeline, Stage};
+
+mod pingpong;
+pub use pingpong::{PingPong, Step};
+
+mod pool;
+pub use pool::{BufferPool, Pooled, PooledInput, PooledOutput, SizeClass};
+
+mod scope;
+pub use scope::{transfer_scope, TransferScope};
+
+mod guard_set;
+pub use guard_set::{join, join_checked, GuardSet, Join, Joined};
+
 // Extern‑Callback (nur für memtrace)
 #[cfg(feature = "memtrace")]
 use {
@@ -19,233 +77,856 @@ use {
 #[cfg(feature = "memtrace")]
 pub extern "C" fn memtrace_callback(
     _evt: cl_event,
-    _status: cl_int,
+    status: cl_int,
     user_data: *mut c_void,
 ) {
     // SAFETY: Pointer was obtained via `Box::into_raw`, so it is non-null and uniquely owned.
     let tok: Box<CopyToken> = unsafe { Box::from_raw(user_data.cast()) };
-    tok.finish();
+    tok.finish_with_status(status);
 }
 
-// OpenCL / Std‑Imports
-use opencl3::{
-    context::Context,
-    memory::{Buffer, CL_MEM_READ_WRITE},
-    command_queue::CommandQueue,
-    event::Event,
-    types::CL_NON_BLOCKING,
-};
-use std::{marker::PhantomData, ptr};
-
-#[cfg(feature = "metrics")]
-use std::time::Instant;
-
-// **Neu**: für die Allocation‑Zähler
-#[cfg(feature = "metrics")]
-use std::sync::atomic::Ordering;
-
-// Fehler‑Typ & cl_try!
-#[derive(thiserror::Error, Debug)]
-pub enum ClError {
-    #[error("OpenCL error code {0}")]
-    Api(i32),
+// Token nur anlegen, wenn Auto-Tracing aktiv ist
+#[cfg(feature = "memtrace")]
+pub(crate) fn trace_begin(dir: Dir, bytes: usize) -> Option<Box<CopyToken>> {
+    is_auto_trace_enabled().then(|| Box::new(start(dir, bytes)))
 }
 
-macro_rules! cl_try {
-    ($expr:expr) => {
-        let err = unsafe { $expr };
-        if err != 0 {
-            return Err(crate::ClError::Api(err));
+// Token an das Event hängen; wird bei CL_COMPLETE abgeschlossen
+#[cfg(feature = "memtrace")]
+pub(crate) fn trace_on_complete<B: Backend>(evt: &B::Event, token_box: Option<Box<CopyToken>>) {
+    if let Some(token_box) = token_box {
+        // Bei Registrierungsfehler läuft der Callback sofort, der Token geht nicht verloren
+        if let Err(e) = B::on_complete(evt, Box::new(move |status| token_box.finish_with_status(status))) {
+            eprintln!("callback failed: {e}");
         }
-    };
+    }
 }
-pub(crate) use cl_try;
 
-impl From<opencl3::error_codes::ClError> for ClError {
-    fn from(err: opencl3::error_codes::ClError) -> Self { ClError::Api(err.0) }
-}
-impl From<i32> for ClError {
-    fn from(code: i32) -> Self { ClError::Api(code) }
-}
+// OpenCL / Std‑Imports
+use opencl3::event::CL_COMPLETE;
+use bytemuck::Pod;
+use std::{marker::PhantomData, mem, ops::Range, sync::{Arc, Condvar, Mutex}, time::Duration};
+
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+// Allocation‑Zähler, Buffer‑IDs
+use std::sync::atomic::{AtomicU64, Ordering};
 
 // Typ‑State‑Marker 
 mod sealed { pub trait Sealed {} }
-pub trait State: sealed::Sealed {}
+pub trait State: sealed::Sealed {
+    // Kommando läuft evtl. noch → Speicher nicht wiederverwenden (Pool)
+    #[doc(hidden)]
+    const PENDING: bool = false;
+
+    // Buffer gehört einem StreamPair (nur dessen Kommandos ordnen ihn ein)
+    #[doc(hidden)]
+    const PAIRED: bool = false;
+}
 
 pub struct Queued;   impl sealed::Sealed for Queued {}   impl State for Queued {}
-pub struct InFlight; impl sealed::Sealed for InFlight {} impl State for InFlight {}
 pub struct Ready;    impl sealed::Sealed for Ready  {}   impl State for Ready  {}
 
+/// In-flight state. `'a` is the lifetime of the host memory the pending
+/// command reads from or writes into (`'static` for device-only commands).
+pub struct InFlight<'a>(PhantomData<&'a ()>);
+impl sealed::Sealed for InFlight<'_> {}
+impl State for InFlight<'_> { const PENDING: bool = true; }
+
+/// Mapped into host memory; only reachable through [`MapRead`] / [`MapWrite`].
+pub struct Mapped;   impl sealed::Sealed for Mapped {}   impl State for Mapped {}
+
+/// States a buffer may be in when it is only written, by the host or a
+/// kernel: freshly allocated (`Queued`) or already initialised (`Ready`).
+pub trait Writable: PairWritable {}
+impl Writable for Queued {}
+impl Writable for Ready {}
+
+/// Last used by a command of a [`StreamPair`], which may still be running.
+/// Only commands of the same pair accept the buffer (they are ordered
+/// behind it); [`StreamPair::into_ready`] hands it back as `Ready`.
+pub struct Paired;   impl sealed::Sealed for Paired {}
+impl State for Paired { const PENDING: bool = true; const PAIRED: bool = true; }
+
+/// States a [`StreamPair`] command may write into (`Queued`, `Ready`,
+/// [`Paired`]); buffers in these states can also be split.
+pub trait PairWritable: State {}
+impl PairWritable for Queued {}
+impl PairWritable for Ready {}
+impl PairWritable for Paired {}
+
+/// Initialised states a [`StreamPair`] command may read from (`Ready`, [`Paired`]).
+pub trait PairReadable: PairWritable {}
+impl PairReadable for Ready {}
+impl PairReadable for Paired {}
+
 //  GPU‑Buffer Wrapper 
 
-pub struct GpuBuffer<S> {
-    buf: Buffer<u8>,
+/// Device buffer of `len` elements of `T`, tracked through the
+/// Queued → InFlight → Ready typestate on backend `B` (OpenCL by default).
+/// `A` fixes the memory flags ([`ReadWrite`] by default); operations the
+/// flags forbid, like `enqueue_read` on a [`HostWriteOnly`] buffer, do not compile.
+///
+/// ```compile_fail
+/// # use hpc_core::{GpuBuffer, HostWriteOnly, Mock, MockContext, MockQueue, Queued, WriteOnly};
+/// let ctx = MockContext::new();
+/// let queue = MockQueue::new(&ctx);
+/// let buf = GpuBuffer::<u8, Queued, Mock, HostWriteOnly<WriteOnly>>::new_in(&ctx, 4).unwrap();
+/// let ready = buf.enqueue_fill(&queue, 0).map(|(b, g)| b.into_ready(g)).unwrap();
+/// let mut out = [0u8; 4];
+/// let _ = ready.enqueue_read(&queue, &mut out); // error: the host may not read this buffer
+/// ```
+pub struct GpuBuffer<T: Pod, S, B: Backend = OpenCl, A: Access = ReadWrite> {
+    buf: B::Mem<T>,
     len: usize,
-    _state: PhantomData<S>,
+    // eindeutig, anders als das Handle nie wiederverwendet (siehe next_id)
+    id: u64,
+    // `id` des Eltern-Buffers bei Sub-Buffern, sonst 0
+    parent: u64,
+    _state: PhantomData<(S, A)>,
 }
 
+/// Result of a non-blocking enqueue: the buffer in flight plus the guard of
+/// its command, or the untouched buffer together with the error.
+pub type Enqueued<'a, T, S, B = OpenCl, A = ReadWrite> =
+    Result<(GpuBuffer<T, InFlight<'a>, B, A>, GpuEventGuard<'a, B>), EnqueueError<GpuBuffer<T, S, B, A>>>;
+
 // Queued 
-impl GpuBuffer<Queued> {
+impl<T: Pod> GpuBuffer<T, Queued> {
+
+    /// Allocates an OpenCL buffer for `len` elements (not bytes).
+    pub fn new(ctx: &opencl3::context::Context, len: usize) -> Result<Self, ClError> {
+        Self::new_in(ctx, len)
+    }
+}
+
+impl<T: Pod, B: Backend, A: DeviceAlloc> GpuBuffer<T, Queued, B, A> {
     
-    pub fn new(ctx: &Context, len: usize) -> Result<Self, ClError> {
+    /// Allocates a buffer for `len` elements on backend `B` with the flags of `A`.
+    ///
+    /// ```compile_fail
+    /// # use hpc_core::{GpuBuffer, Mock, MockContext, Queued, UseHostPtr};
+    /// let ctx = MockContext::new();
+    /// // error: host-backed buffers come only from `from_host`
+    /// let buf = GpuBuffer::<u8, Queued, Mock, UseHostPtr>::new_in(&ctx, 4);
+    /// ```
+    pub fn new_in(ctx: &B::Context, len: usize) -> Result<Self, ClError> {
 
         #[cfg(feature = "metrics")]
         {
             ALLOCS.fetch_add(1, Ordering::Relaxed);
-            ALLOC_BYTES.fetch_add(len, Ordering::Relaxed);
+            ALLOC_BYTES.fetch_add(len * mem::size_of::<T>(), Ordering::Relaxed);
         }
 
         #[cfg(feature="metrics")]
         let t = Instant::now();
 
-        let buf = Buffer::<u8>::create(ctx, CL_MEM_READ_WRITE, len, ptr::null_mut())?;
+        let buf = B::alloc::<T>(ctx, len, A::FLAGS)
+            .map_err(|e| e.context("GpuBuffer::new", Some(len * mem::size_of::<T>())))?;
 
         #[cfg(feature="metrics")]
         record("GpuBuffer::new", t);
 
-        Ok(Self { buf, len, _state: PhantomData })
+        Ok(Self { buf, len, id: next_id(), parent: 0, _state: PhantomData })
+    }
+}
+
+impl<T: Pod, B: Backend, A: Access> GpuBuffer<T, Queued, B, A> {
+
+    #[deprecated(note = "relabels only; use `hpc_core::launch` to run a kernel through the typestate")]
+    pub fn launch(self) -> GpuBuffer<T, InFlight<'static>, B, A> {
+        #[cfg(feature="metrics")] record("launch", Instant::now());
+        self.into_state()
+    }
+}
+
+impl<'h, T: Pod, B: Backend, K: KernelAccess> GpuBuffer<T, Ready, B, UseHostPtr<'h, K>> {
+
+    /// Wraps `host` as device buffer (`CL_MEM_USE_HOST_PTR`), borrowing it
+    /// for the buffer's lifetime and that of every guard of its commands
+    /// (these wait on drop). The slice holds the initial contents; results
+    /// come back through `enqueue_read` or `map_read`.
+    ///
+    /// # Safety
+    ///
+    /// No guard of a command on the buffer may be leaked (`mem::forget`,
+    /// …): every command accesses `host`, which is borrowed only until the
+    /// guard is dropped or waited on.
+    ///
+    /// ```compile_fail
+    /// # use hpc_core::{GpuBuffer, Mock, MockContext, MockQueue, Ready, UseHostPtr};
+    /// let ctx = MockContext::new();
+    /// let queue = MockQueue::new(&ctx);
+    /// let mut host = vec![0u32; 4];
+    /// let buf = unsafe { GpuBuffer::<u32, Ready, Mock, UseHostPtr>::from_host(&ctx, &mut host) }.unwrap();
+    /// let (inflight, _guard) = buf.enqueue_fill(&queue, 7).unwrap();
+    /// drop(inflight);
+    /// drop(host); // error: the pending fill still borrows `host`
+    /// ```
+    pub unsafe fn from_host(ctx: &B::Context, host: &'h mut [T]) -> Result<Self, ClError> {
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let (len, bytes) = (host.len(), mem::size_of_val(host));
+        // SAFETY: `host` bleibt über `'h` exklusiv geliehen (Marker im Typ),
+        // Guards der Kommandos warten beim Drop und werden nicht geleakt
+        let buf = unsafe { B::alloc_use_host::<T>(ctx, host, K::FLAGS) }
+            .map_err(|e| e.context("GpuBuffer::from_host", Some(bytes)))?;
+
+        #[cfg(feature="metrics")]
+        record("GpuBuffer::from_host", t);
+
+        Ok(Self { buf, len, id: next_id(), parent: 0, _state: PhantomData })
+    }
+}
+
+impl<T: Pod, B: Backend, A: HostWrite> GpuBuffer<T, Queued, B, A> {
+
+    /// Uploads the whole buffer; `host.len()` must equal [`len`](Self::len).
+    ///
+    /// # Safety
+    ///
+    /// The returned guard must not be leaked (`mem::forget`, a cycle of
+    /// `Rc`s, …): `host` is borrowed only until the guard is dropped or
+    /// waited on, a leaked guard lets safe code free it while the transfer
+    /// still reads from it. [`transfer_scope`] offers the same transfer
+    /// without this requirement.
+    pub unsafe fn enqueue_write<'a>(
+        self,
+        queue: &B::Queue,
+        host: &'a [T],
+    ) -> Enqueued<'a, T, Queued, B, A>
+    where
+        A: 'a,
+    {
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.enqueue_write_after(queue, host, &[]) }
+    }
+
+    /// Like [`enqueue_write`](Self::enqueue_write), but starts only after
+    /// the commands of `deps` (on any queue) have completed.
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_write`](Self::enqueue_write).
+    pub unsafe fn enqueue_write_after<'a>(
+        self,
+        queue: &B::Queue,
+        host: &'a [T],
+        deps: &[&GpuEventGuard<'_, B>],
+    ) -> Enqueued<'a, T, Queued, B, A>
+    where
+        A: 'a,
+    {
+        if host.len() != self.len {
+            let err = ClError::SizeMismatch { expected: self.len, actual: host.len() };
+            return Err(EnqueueError::new(self, err));
+        }
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.write_unchecked(queue, 0, host, &wait_list(deps)) }
     }
+}
 
+// Host → Device, auch teilweise (z.B. Halo-Zeilen) in einen Ready-Buffer
+impl<T: Pod, S: Writable, B: Backend, A: HostWrite> GpuBuffer<T, S, B, A> {
+
+    /// Uploads `host` to elements `offset..offset + host.len()`; an empty
+    /// `host` fails with [`ClError::InvalidArgs`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_write`](GpuBuffer::enqueue_write).
+    pub unsafe fn enqueue_write_at<'a>(
+        self,
+        queue: &B::Queue,
+        offset: usize,
+        host: &'a [T],
+    ) -> Enqueued<'a, T, S, B, A>
+    where
+        A: 'a,
+    {
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.enqueue_write_at_after(queue, offset, host, &[]) }
+    }
 
-    pub fn enqueue_write(
+    /// Like [`enqueue_write_at`](Self::enqueue_write_at), ordered after `deps`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_write`](GpuBuffer::enqueue_write).
+    pub unsafe fn enqueue_write_at_after<'a>(
+        self,
+        queue: &B::Queue,
+        offset: usize,
+        host: &'a [T],
+        deps: &[&GpuEventGuard<'_, B>],
+    ) -> Enqueued<'a, T, S, B, A>
+    where
+        A: 'a,
+    {
+        if let Err(err) = self.check_range(offset, host.len()) {
+            return Err(EnqueueError::new(self, err));
+        }
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.write_unchecked(queue, offset, host, &wait_list(deps)) }
+    }
+
+    // SAFETY-Vertrag wie enqueue_write: der Guard darf nicht geleakt werden
+    pub(crate) unsafe fn write_unchecked<'a>(
         mut self,
-        queue: &CommandQueue,
-        host: &[u8],
-    ) -> Result<(GpuBuffer<InFlight>, GpuEventGuard), ClError> {
+        queue: &B::Queue,
+        offset: usize,
+        host: &'a [T],
+        wait: &[&B::Event],
+    ) -> Enqueued<'a, T, S, B, A>
+    where
+        A: 'a,
+    {
 
         #[cfg(feature="metrics")]
         let t = Instant::now();
 
         #[cfg(feature="memtrace")]
-        let token_box = if is_auto_trace_enabled() {
-            Some(Box::new(start(Dir::H2D, host.len())))
-        } else {
-            None
+        let token_box = trace_begin(Dir::H2D, mem::size_of_val(host));
+        // SAFETY: `host` bleibt über `'a` im Guard geliehen, Drop wartet auf das
+        // Event; der Aufrufer garantiert, dass der Guard nicht geleakt wird
+        let evt = match unsafe { B::write(queue, &mut self.buf, offset, host, wait) } {
+            Ok(evt) => evt,
+            Err(e)  => {
+                let err = e.context("enqueue_write", Some(mem::size_of_val(host)));
+                return Err(EnqueueError::new(self, err));
+            }
         };
-        let evt = queue.enqueue_write_buffer(
-            &mut self.buf,
-            CL_NON_BLOCKING,
-            0,
-            host,
-            &[],
-        )?;
 
         #[cfg(feature="memtrace")]
-        if let Some(token_box) = token_box {
-            use opencl3::event::CL_COMPLETE;
-            // SAFETY: `Box::into_raw` yields a non-null, uniquely-owned pointer.
-        // Ownership is transferred to the OpenCL runtime and will be
-        // reclaimed in `memtrace_callback` (or immediately below on error).
-            let ptr = Box::into_raw(token_box) as *mut c_void;
-            if let Err(e) = evt.set_callback(CL_COMPLETE, memtrace_callback, ptr) {
-                eprintln!("callback failed: {e}");
-                 // SAFETY: `set_callback` returned an error, so the runtime did NOT
-            // take ownership of `ptr`. We therefore re-create the Box and drop
-                // it (via `finish`) to avoid a leak.
-                unsafe { Box::from_raw(ptr.cast::<CopyToken>()) }.finish();
-            }
-        }
+        trace_on_complete::<B>(&evt, token_box);
 
         #[cfg(feature="metrics")]
         record("enqueue_write", t);
 
+        keep_alive::<T, B>(&self.buf, &evt);
+        let ids = [self.id];
         Ok((
-            GpuBuffer { buf: self.buf, len: self.len, _state: PhantomData::<InFlight> },
-            GpuEventGuard { evt },
+            self.into_state(),
+            GpuEventGuard::new(evt, true, &ids),
         ))
     }
-
-    pub fn launch(self) -> GpuBuffer<InFlight> {
-        #[cfg(feature="metrics")] record("launch", Instant::now());
-        GpuBuffer { buf: self.buf, len: self.len, _state: PhantomData }
-    }
 }
 
 // Ready → Host (D2H)
-impl GpuBuffer<Ready> {
+impl<T: Pod, B: Backend, A: HostRead> GpuBuffer<T, Ready, B, A> {
+
+    /// Downloads the whole buffer; `host_out.len()` must equal [`len`](Self::len).
+    ///
+    /// # Safety
+    ///
+    /// The returned guard must not be leaked (`mem::forget`, a cycle of
+    /// `Rc`s, …): `host_out` is borrowed only until the guard is dropped or
+    /// waited on, a leaked guard lets safe code free it while the transfer
+    /// still writes into it. [`transfer_scope`] offers the same transfer
+    /// without this requirement.
+    pub unsafe fn enqueue_read<'a>(
+        self,
+        queue: &B::Queue,
+        host_out: &'a mut [T],
+    ) -> Enqueued<'a, T, Ready, B, A>
+    where
+        A: 'a,
+    {
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.enqueue_read_after(queue, host_out, &[]) }
+    }
 
-    pub fn enqueue_read(
-        mut self,
-        queue: &CommandQueue,
-        host_out: &mut [u8],
-    ) -> Result<(GpuBuffer<InFlight>, GpuEventGuard), ClError> {
+    /// Like [`enqueue_read`](Self::enqueue_read), but starts only after the
+    /// commands of `deps` (on any queue) have completed.
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_read`](Self::enqueue_read).
+    pub unsafe fn enqueue_read_after<'a>(
+        self,
+        queue: &B::Queue,
+        host_out: &'a mut [T],
+        deps: &[&GpuEventGuard<'_, B>],
+    ) -> Enqueued<'a, T, Ready, B, A>
+    where
+        A: 'a,
+    {
+        if host_out.len() != self.len {
+            let err = ClError::SizeMismatch { expected: self.len, actual: host_out.len() };
+            return Err(EnqueueError::new(self, err));
+        }
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.read_unchecked(queue, 0, host_out, &wait_list(deps)) }
+    }
+
+    /// Downloads the elements in `range`; `host_out.len()` must equal `range.len()`.
+    /// An inverted range fails with [`ClError::OutOfRange`], an empty one
+    /// with [`ClError::InvalidArgs`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_read`](Self::enqueue_read).
+    pub unsafe fn enqueue_read_range<'a>(
+        self,
+        queue: &B::Queue,
+        range: Range<usize>,
+        host_out: &'a mut [T],
+    ) -> Enqueued<'a, T, Ready, B, A>
+    where
+        A: 'a,
+    {
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.enqueue_read_range_after(queue, range, host_out, &[]) }
+    }
+
+    /// Like [`enqueue_read_range`](Self::enqueue_read_range), ordered after `deps`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_read`](Self::enqueue_read).
+    pub unsafe fn enqueue_read_range_after<'a>(
+        self,
+        queue: &B::Queue,
+        range: Range<usize>,
+        host_out: &'a mut [T],
+        deps: &[&GpuEventGuard<'_, B>],
+    ) -> Enqueued<'a, T, Ready, B, A>
+    where
+        A: 'a,
+    {
+        let count = match self.check_span(&range) {
+            Ok(count) => count,
+            Err(err)  => return Err(EnqueueError::new(self, err)),
+        };
+        if host_out.len() != count {
+            let err = ClError::SizeMismatch { expected: count, actual: host_out.len() };
+            return Err(EnqueueError::new(self, err));
+        }
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.read_unchecked(queue, range.start, host_out, &wait_list(deps)) }
+    }
+
+    // SAFETY-Vertrag wie enqueue_read: der Guard darf nicht geleakt werden
+    pub(crate) unsafe fn read_unchecked<'a>(
+        self,
+        queue: &B::Queue,
+        offset: usize,
+        host_out: &'a mut [T],
+        wait: &[&B::Event],
+    ) -> Enqueued<'a, T, Ready, B, A>
+    where
+        A: 'a,
+    {
 
         #[cfg(feature="metrics")]
         let t = Instant::now();
 
         #[cfg(feature="memtrace")]
-        let token_box = if is_auto_trace_enabled() {
-            Some(Box::new(start(Dir::D2H, host_out.len())))
-        } else {
-            None
+        let token_box = trace_begin(Dir::D2H, mem::size_of_val(host_out));
+
+        // SAFETY: `host_out` bleibt über `'a` im Guard geliehen, Drop wartet auf
+        // das Event; der Aufrufer garantiert, dass der Guard nicht geleakt wird
+        let evt = match unsafe { B::read(queue, &self.buf, offset, host_out, wait) } {
+            Ok(evt) => evt,
+            Err(e)  => {
+                let err = e.context("enqueue_read", Some(mem::size_of_val(host_out)));
+                return Err(EnqueueError::new(self, err));
+            }
         };
 
-        let evt = queue.enqueue_read_buffer(
-            &mut self.buf,
-            CL_NON_BLOCKING,
-            0,
-            host_out,
-            &[],
-        )?;
-
         #[cfg(feature="memtrace")]
-        if let Some(token_box) = token_box {
-            use opencl3::event::CL_COMPLETE;
-            // SAFETY: `Box::into_raw` yields a non-null, uniquely-owned pointer.
-    // Ownership is transferred to the OpenCL runtime and will be
-    // reclaimed in `memtrace_callback` (or immediately below on error).
-            let ptr = Box::into_raw(token_box) as *mut c_void;
-            if let Err(e) = evt.set_callback(CL_COMPLETE, memtrace_callback, ptr) {
-                eprintln!("callback failed: {e}");
-                // SAFETY: `set_callback` returned an error, so the runtime did NOT
-        // take ownership of `ptr`. We therefore re-create the Box and drop
-        // it (via `finish`) to avoid a leak.
-                unsafe { Box::from_raw(ptr.cast::<CopyToken>()) }.finish();
-            }
-        }
+        trace_on_complete::<B>(&evt, token_box);
 
         #[cfg(feature="metrics")]
         record("enqueue_read", t);
 
+        keep_alive::<T, B>(&self.buf, &evt);
+        let ids = [self.id];
         Ok((
-            GpuBuffer { buf: self.buf, len: self.len, _state: PhantomData::<InFlight> },
-            GpuEventGuard { evt },
+            self.into_state(),
+            GpuEventGuard::new(evt, true, &ids),
         ))
     }
 }
 
 // InFlight 
-impl GpuBuffer<InFlight> {
+impl<'a, T: Pod, B: Backend, A: Access> GpuBuffer<T, InFlight<'a>, B, A> {
    
-    pub fn complete(self, evt: Event) -> GpuBuffer<Ready> {
-        let _g = GpuEventGuard { evt };
+    pub fn complete(self, evt: B::Event) -> GpuBuffer<T, Ready, B, A> {
         #[cfg(feature="metrics")] record("complete", Instant::now());
-        GpuBuffer { buf: self.buf, len: self.len, _state: PhantomData }
+        GpuEventGuard::<'a, B>::new(evt, true, &[]).finish();
+        self.into_state()
     }
 
-    pub fn into_ready(self, _g: GpuEventGuard) -> GpuBuffer<Ready> {
+    pub fn into_ready(self, g: GpuEventGuard<'a, B>) -> GpuBuffer<T, Ready, B, A> {
         #[cfg(feature="metrics")] record("into_ready", Instant::now());
-        GpuBuffer { buf: self.buf, len: self.len, _state: PhantomData }
+        g.finish();
+        self.into_state()
+    }
+
+    /// Like [`into_ready`](Self::into_ready), but a command that terminated
+    /// abnormally hands the buffer back as `Queued` (its contents are
+    /// undefined) together with [`ClError::CommandFailed`].
+    pub fn into_ready_checked(self, g: GpuEventGuard<'a, B>) -> Checked<T, B, A> {
+        match g.wait() {
+            Ok(())   => Ok(self.into_state()),
+            Err(err) => Err(EnqueueError::new(self.into_state(), err)),
+        }
+    }
+
+    /// Non-blocking: `Ok(Ready)` once the command has completed, otherwise
+    /// buffer and guard come back as [`NotReady::Pending`]. A command that
+    /// terminated abnormally yields [`NotReady::Failed`] with the buffer as
+    /// `Queued`, since its contents are undefined.
+    pub fn try_complete(self, guard: GpuEventGuard<'a, B>) -> TryComplete<'a, T, B, A> {
+        match guard.is_complete() {
+            Ok(true)   => Ok(self.into_ready(guard)),
+            Ok(false)  => Err(NotReady::Pending { buffer: self, guard }),
+            Err(error) => {
+                guard.dismiss();
+                Err(NotReady::Failed { buffer: self.into_state(), error })
+            }
+        }
+    }
+
+    /// Waits at most `timeout` for the command: `Ok(Ready)` once it has
+    /// completed, otherwise buffer and guard come back as
+    /// [`NotReady::Pending`] (or [`NotReady::Failed`], as with
+    /// [`try_complete`](Self::try_complete)) instead of blocking forever.
+    pub fn into_ready_timeout(self, mut guard: GpuEventGuard<'a, B>, timeout: Duration) -> TryComplete<'a, T, B, A> {
+        match guard.wait_timeout(timeout) {
+            Ok(true)   => Ok(self.into_state()),
+            Ok(false)  => Err(NotReady::Pending { buffer: self, guard }),
+            Err(error) => Err(NotReady::Failed { buffer: self.into_state(), error }),
+        }
+    }
+}
+
+// InFlight → nächstes Kommando hinter dem laufenden, ohne zu warten.
+// `guard` ist der Guard des laufenden Kommandos, er bleibt beim Aufrufer.
+impl<'g, T: Pod, B: Backend, A: HostRead> GpuBuffer<T, InFlight<'g>, B, A> {
+
+    /// Like `enqueue_read_after` on a Ready buffer, for a buffer whose
+    /// command `guard` is still pending: the download starts after it and
+    /// `deps` without blocking the host. A `guard` of a command on another
+    /// buffer is rejected with [`ClError::GuardMismatch`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_read`](GpuBuffer::enqueue_read).
+    pub unsafe fn enqueue_read_after<'a>(
+        self,
+        guard: &GpuEventGuard<'g, B>,
+        queue: &B::Queue,
+        host_out: &'a mut [T],
+        deps: &[&GpuEventGuard<'_, B>],
+    ) -> Enqueued<'a, T, InFlight<'g>, B, A>
+    where
+        A: 'a,
+    {
+        // SAFETY: Vertrag des Aufrufers
+        self.chain(guard, deps, |buf: GpuBuffer<T, Ready, B, A>, deps| unsafe { buf.enqueue_read_after(queue, host_out, deps) })
+    }
+
+    /// Like [`enqueue_read_after`](Self::enqueue_read_after) for the elements in `range`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_read`](GpuBuffer::enqueue_read).
+    pub unsafe fn enqueue_read_range_after<'a>(
+        self,
+        guard: &GpuEventGuard<'g, B>,
+        queue: &B::Queue,
+        range: Range<usize>,
+        host_out: &'a mut [T],
+        deps: &[&GpuEventGuard<'_, B>],
+    ) -> Enqueued<'a, T, InFlight<'g>, B, A>
+    where
+        A: 'a,
+    {
+        // SAFETY: Vertrag des Aufrufers
+        self.chain(guard, deps, |buf: GpuBuffer<T, Ready, B, A>, deps| unsafe {
+            buf.enqueue_read_range_after(queue, range, host_out, deps)
+        })
+    }
+}
+
+impl<'g, T: Pod, B: Backend, A: HostWrite> GpuBuffer<T, InFlight<'g>, B, A> {
+
+    /// Like `enqueue_write_at_after` on a Ready buffer, for a buffer whose
+    /// command `guard` is still pending: the upload starts after it and `deps`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_write`](GpuBuffer::enqueue_write).
+    pub unsafe fn enqueue_write_at_after<'a>(
+        self,
+        guard: &GpuEventGuard<'g, B>,
+        queue: &B::Queue,
+        offset: usize,
+        host: &'a [T],
+        deps: &[&GpuEventGuard<'_, B>],
+    ) -> Enqueued<'a, T, InFlight<'g>, B, A>
+    where
+        A: 'a,
+    {
+        // SAFETY: Vertrag des Aufrufers
+        self.chain(guard, deps, |buf: GpuBuffer<T, Ready, B, A>, deps| unsafe {
+            buf.enqueue_write_at_after(queue, offset, host, deps)
+        })
     }
 }
 
+impl<'g, T: Pod, B: Backend, A: Access> GpuBuffer<T, InFlight<'g>, B, A> {
+    // Zustand nur für den Aufruf umetikettiert: das Kommando wartet auf `guard`,
+    // bei Fehler kommt der Buffer wieder als InFlight zurück
+    fn chain<'a, S: State>(
+        self,
+        guard: &GpuEventGuard<'g, B>,
+        deps: &[&GpuEventGuard<'_, B>],
+        f: impl FnOnce(GpuBuffer<T, S, B, A>, &[&GpuEventGuard<'_, B>]) -> Enqueued<'a, T, S, B, A>,
+    ) -> Enqueued<'a, T, InFlight<'g>, B, A> {
+        let mut all = Vec::with_capacity(deps.len() + 1);
+        all.push(guard);
+        all.extend_from_slice(deps);
+        if let Err(err) = guard.covers(self.id) {
+            return Err(EnqueueError::new(self, err));
+        }
+        f(self.into_state(), &all).map_err(|e| {
+            let (buf, err) = e.into_parts();
+            EnqueueError::new(buf.into_state(), err)
+        })
+    }
+}
+
+/// Result of [`GpuBuffer::try_complete`].
+pub type TryComplete<'a, T, B = OpenCl, A = ReadWrite> = Result<GpuBuffer<T, Ready, B, A>, NotReady<'a, T, B, A>>;
+
+/// Result of [`GpuBuffer::into_ready_checked`].
+pub type Checked<T, B = OpenCl, A = ReadWrite> = Result<GpuBuffer<T, Ready, B, A>, EnqueueError<GpuBuffer<T, Queued, B, A>>>;
+
+/// Outcome of [`GpuBuffer::try_complete`] / [`GpuBuffer::into_ready_timeout`]
+/// when the buffer is not Ready.
+pub enum NotReady<'a, T: Pod, B: Backend = OpenCl, A: Access = ReadWrite> {
+    Pending { buffer: GpuBuffer<T, InFlight<'a>, B, A>, guard: GpuEventGuard<'a, B> },
+    Failed { buffer: GpuBuffer<T, Queued, B, A>, error: ClError },
+}
+
 // Accessors (alle States) 
-impl<S> GpuBuffer<S> {
+impl<T: Pod, S, B: Backend, A: Access> GpuBuffer<T, S, B, A> {
     
-    pub fn raw(&self) -> &Buffer<u8> { &self.buf }
+    pub fn raw(&self) -> &B::Mem<T> { &self.buf }
     
-    pub fn raw_mut(&mut self) -> &mut Buffer<u8> { &mut self.buf }
+    pub fn raw_mut(&mut self) -> &mut B::Mem<T> { &mut self.buf }
     
+    /// Length in elements of `T`.
     pub fn len(&self) -> usize { self.len }
+
+    /// Length in bytes.
+    pub fn byte_len(&self) -> usize { self.len * mem::size_of::<T>() }
+
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    // Bereich `offset..offset + count` muss im Buffer liegen
+    // leere Bereiche lehnt OpenCL ab (size 0 → CL_INVALID_VALUE), daher vorher
+    fn check_range(&self, offset: usize, count: usize) -> Result<(), ClError> {
+        match offset.checked_add(count) {
+            _ if count == 0 => Err(ClError::InvalidArgs { reason: "empty range" }),
+            Some(end) if end <= self.len => Ok(()),
+            _ => Err(ClError::OutOfRange { offset, count, len: self.len }),
+        }
+    }
+
+    // Elementzahl von `range`; verkehrt (start > end) ist OutOfRange
+    fn check_span(&self, range: &Range<usize>) -> Result<usize, ClError> {
+        let Some(count) = range.end.checked_sub(range.start) else {
+            return Err(ClError::OutOfRange { offset: range.start, count: 0, len: self.len });
+        };
+        self.check_range(range.start, count).map(|_| count)
+    }
+
+    // Reiner Zustandswechsel, Event-Handling liegt beim Aufrufer
+    pub(crate) fn into_state<S2>(self) -> GpuBuffer<T, S2, B, A> {
+        GpuBuffer { buf: self.buf, len: self.len, id: self.id, parent: self.parent, _state: PhantomData }
+    }
+}
+
+impl<T: Pod, S: State, B: Backend, A: Access> GpuBuffer<T, S, B, A> {
+    // Identität für die StreamPair-Reihenfolge
+    pub(crate) fn mem_key(&self) -> MemKey {
+        MemKey {
+            id: self.id,
+            parent: (self.parent != 0).then_some(self.parent),
+            paired: S::PAIRED,
+        }
+    }
 }
 
-// Guard (wartet bei Drop auf Event) 
-pub struct GpuEventGuard { evt: Event }
-impl Drop for GpuEventGuard {
+// Guard (wartet bei Drop nur auf Host-Transfers) 
+
+/// Keeps the host slice of a non-blocking transfer borrowed until the
+/// command has finished: the slice can neither be dropped nor touched while
+/// the guard is alive, and dropping a transfer's guard waits for the event
+/// (bounded waits: [`wait_timeout`](Self::wait_timeout)).
+/// Guards of device-only commands (kernels, copies, fills) drop without
+/// blocking; the runtime keeps their buffers alive until they complete.
+/// Commands on a [`UseHostPtr`] buffer count as host transfers and wait.
+/// In async code, `guard.await` waits without blocking (see [`EventFuture`]).
+///
+/// A failed command seen on drop is handled by the [`DropErrorPolicy`];
+/// [`wait`](Self::wait) reports it to the caller instead.
+///
+/// The guard is not leak-safe: the borrow ends with its `Drop`, so after
+/// `mem::forget(guard)` the host slice could be freed while the transfer
+/// still uses it. That is why the host transfers (and
+/// [`from_host`](GpuBuffer::from_host)) are `unsafe fn`s; [`transfer_scope`]
+/// offers them safely.
+///
+/// ```compile_fail
+/// # use hpc_core::{GpuBuffer, Queued};
+/// # fn f(ctx: &opencl3::context::Context, q: &opencl3::command_queue::CommandQueue) {
+/// let mut host = vec![0u8; 4];
+/// let buf = GpuBuffer::<u8, Queued>::new(ctx, 4).unwrap();
+/// let (inflight, guard) = unsafe { buf.enqueue_write(q, &host) }.unwrap();
+/// host[0] = 1; // error: `host` is still borrowed by the in-flight transfer
+/// let _ready = inflight.into_ready(guard);
+/// # }
+/// ```
+pub struct GpuEventGuard<'a, B: Backend = OpenCl> {
+    evt: B::Event,
+    // Kommando greift auf geliehenen Host-Speicher zu → Drop muss warten
+    host: bool,
+    // Status wurde dem Aufrufer bereits gemeldet → Drop schweigt
+    checked: bool,
+    // Completion-Signal für wait_timeout, Callback nur einmal registriert
+    signal: Option<Signal>,
+    // IDs der Buffer des Kommandos: *_after prüft, ob der Guard zum Buffer gehört
+    ids: Box<[u64]>,
+    _host: PhantomData<&'a ()>,
+}
+
+// gesetzt vom on_complete-Callback, geweckt über die Condvar
+type Signal = Arc<(Mutex<bool>, Condvar)>;
+impl<B: Backend> Drop for GpuEventGuard<'_, B> {
    
-    fn drop(&mut self) { let _ = self.evt.wait(); }
+    fn drop(&mut self) {
+        if self.host { let _ = B::wait(&self.evt); }
+        // ohne Warten nur ein bereits bekannter Fehlschlag
+        if !self.checked && let Err(e) = self.is_complete() {
+            report_drop_error(&e);
+        }
+    }
+}
+
+impl<B: Backend> GpuEventGuard<'_, B> {
+    // `host`: Transfer von/in Host-Speicher der Lebensdauer `'a` (auch UseHostPtr),
+    // `ids`: Buffer, auf denen das Kommando läuft
+    pub(crate) fn new(evt: B::Event, host: bool, ids: &[u64]) -> Self {
+        Self { evt, host, checked: false, signal: None, ids: ids.into(), _host: PhantomData }
+    }
+
+    // Guard eines Kommandos auf dem Buffer `id`?
+    pub(crate) fn covers(&self, id: u64) -> Result<(), ClError> {
+        match self.ids.contains(&id) {
+            true  => Ok(()),
+            false => Err(ClError::GuardMismatch),
+        }
+    }
+
+    // Blockierend abwarten, z. B. vor dem Wechsel nach Ready
+    pub(crate) fn finish(self) { let _ = B::wait(&self.evt); }
+
+    // Fehler ist anderweitig gemeldet (NotReady::Failed, EventFuture)
+    pub(crate) fn dismiss(mut self) { self.checked = true; }
+
+    // Erfolgreich abgewartet (GuardSet): Drop wartet und prüft nicht erneut
+    pub(crate) fn settle(mut self) {
+        self.host = false;
+        self.checked = true;
+    }
+}
+
+// Speicher von `mem` bis zum Ende von `evt` halten; ohne Callback bleibt nur Warten
+pub(crate) fn keep_alive<T: Pod, B: Backend>(mem: &B::Mem<T>, evt: &B::Event) {
+    if B::keep_alive(mem, evt).is_err() {
+        let _ = B::wait(evt);
+    }
+}
+
+// Buffer, ggf. Eltern-Buffer; `paired`: gehört einem StreamPair
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub struct MemKey {
+    pub(crate) id: u64,
+    pub(crate) parent: Option<u64>,
+    pub(crate) paired: bool,
+}
+
+// Buffer-IDs, ab 1 (0 = keine)
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+pub(crate) fn next_id() -> u64 { NEXT_ID.fetch_add(1, Ordering::Relaxed) }
+
+// Events der Guards als Wait-List, ohne die Guards zu verbrauchen
+pub(crate) fn wait_list<'g, B: Backend>(deps: &[&'g GpuEventGuard<'_, B>]) -> Vec<&'g B::Event> {
+    deps.iter().map(|g| &g.evt).collect()
+}
+
+impl<B: Backend> GpuEventGuard<'_, B> {
+
+    /// Blocks until the command has finished: `Ok(())` once it is
+    /// `CL_COMPLETE`, `Err(ClError::CommandFailed)` if it terminated with a
+    /// negative status.
+    pub fn wait(mut self) -> Result<(), ClError> {
+        self.checked = true;
+        let waited = B::wait(&self.evt);
+        self.is_complete()?;
+        waited.map_err(|e| e.context("wait", None))
+    }
+
+    /// Waits at most `timeout`: `Ok(true)` once the command has completed,
+    /// `Ok(false)` if it is still running, `Err(ClError::CommandFailed)` if
+    /// it terminated with a negative status.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<bool, ClError> {
+        let done = match &self.signal {
+            Some(done) => done.clone(),
+            None => {
+                let done: Signal = Arc::new((Mutex::new(false), Condvar::new()));
+                let signal = done.clone();
+                B::on_complete(&self.evt, Box::new(move |_| {
+                    *signal.0.lock().unwrap_or_else(|e| e.into_inner()) = true;
+                    signal.1.notify_all();
+                })).map_err(|e| e.context("wait_timeout", None))?;
+                self.signal.insert(done).clone()
+            }
+        };
+
+        let finished = done.0.lock().unwrap_or_else(|e| e.into_inner());
+        let (finished, _) = done.1.wait_timeout_while(finished, timeout, |f| !*f).unwrap_or_else(|e| e.into_inner());
+        if !*finished {
+            return Ok(false);
+        }
+        // Fehlschlag geht an den Aufrufer, nicht an die Drop-Policy
+        let status = self.is_complete();
+        self.checked |= status.is_err();
+        status
+    }
+
+    /// Non-blocking status check: `Ok(true)` once the command is `CL_COMPLETE`,
+    /// `Err(ClError::CommandFailed)` if it terminated with a negative status.
+    pub fn is_complete(&self) -> Result<bool, ClError> {
+        let status = B::status(&self.evt).map_err(|e| e.context("is_complete", None))?;
+        match status {
+            CL_COMPLETE => Ok(true),
+            s if s < 0  => Err(ClError::CommandFailed { code: ClCode(s) }),
+            _           => Ok(false),
+        }
+    }
 }
 
 // **Neu**: Re-Export 
 #[cfg(feature = "metrics")]
-pub use metrics::{ALLOCS, ALLOC_BYTES};
\ No newline at end of file
+pub use metrics::{ALLOCS, ALLOC_BYTES, POOL_HITS, POOL_MISSES, POOL_EVICTIONS};
\ No newline at end of file
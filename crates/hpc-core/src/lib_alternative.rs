@@ -413,52 +413,3 @@ impl GpuEventGuard {
 // **Performance**: Re-Export für Allocation‑Zähler
 #[cfg(feature = "metrics")]
 pub use metrics::{ALLOCS, ALLOC_BYTES};
-
-// Buffer Pool für High-Performance Use Cases
-use std::collections::HashMap;
-
-/// **Performance**: Buffer Pool für Recycling
-pub struct BufferPool {
-    free_buffers: HashMap<usize, Vec<Buffer<u8>>>,
-    ctx: Context,
-}
-
-impl BufferPool {
-    pub fn new(ctx: Context) -> Self {
-        Self {
-            free_buffers: HashMap::new(),
-            ctx,
-        }
-    }
-
-    /// **Performance**: Vermeide Allokationen durch Recycling
-    pub fn get_or_create(&mut self, size: usize) -> Result<GpuBuffer<Queued>, ClError> {
-        if let Some(buffers) = self.free_buffers.get_mut(&size) {
-            if let Some(buf) = buffers.pop() {
-                return Ok(GpuBuffer {
-                    inner: GpuBufferInner { buf, len: size },
-                    _state: PhantomData,
-                });
-            }
-        }
-        
-        GpuBuffer::new(&self.ctx, size)
-    }
-
-    /// **Performance**: Buffer zurück in Pool (konsumiert den Buffer)
-    pub fn return_buffer<S>(&mut self, buffer: GpuBuffer<S>) {
-        let size = buffer.inner.len;
-        
-        // **Fix**: ManuallyDrop verhindert Drop-Probleme
-        use std::mem::ManuallyDrop;
-        let buffer = ManuallyDrop::new(buffer);
-        
-        // Safety: Wir nehmen Ownership und verhindern Drop
-        let inner_buf = unsafe { ptr::read(&buffer.inner.buf) };
-        
-        self.free_buffers
-            .entry(size)
-            .or_insert_with(Vec::new)
-            .push(inner_buf);
-    }
-}
\ No newline at end of file
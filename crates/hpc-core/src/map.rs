@@ -0,0 +1,144 @@
+//! src/map.rs
+//
+// Map/Unmap: Ready → Mapped → Ready mit geliehener Host-Sicht. Auf
+// integrierten GPUs und CPU-Devices ohne Kopie (anders als enqueue_read).
+
+use crate::{Access, Backend, EnqueueError, GpuBuffer, HostRead, HostWrite, Mapped, OpenCl, Queued, Ready, ReadWrite};
+use bytemuck::Pod;
+use std::{mem, ops::{Deref, DerefMut}, slice};
+
+#[cfg(feature = "metrics")]
+use crate::record;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Result of an unmap: the Ready buffer, or the buffer as `Queued` (contents
+/// undefined) together with the error.
+pub type Unmapped<T, B = OpenCl, A = ReadWrite> =
+    Result<GpuBuffer<T, Ready, B, A>, EnqueueError<GpuBuffer<T, Queued, B, A>>>;
+
+impl<T: Pod, B: Backend, A: HostRead> GpuBuffer<T, Ready, B, A> {
+
+    /// Maps the buffer for reading; blocks until the view is valid.
+    pub fn map_read(self, queue: &B::Queue) -> Result<MapRead<'_, T, B, A>, EnqueueError<Self>> {
+        Mapping::new(self, queue, false).map(MapRead)
+    }
+}
+
+impl<T: Pod, B: Backend, A: HostRead + HostWrite> GpuBuffer<T, Ready, B, A> {
+
+    /// Maps the buffer for reading and writing; blocks until the view is
+    /// valid. Writes reach the device when the view is unmapped.
+    pub fn map_write(self, queue: &B::Queue) -> Result<MapWrite<'_, T, B, A>, EnqueueError<Self>> {
+        Mapping::new(self, queue, true).map(MapWrite)
+    }
+}
+
+// Gemeinsamer Teil von MapRead/MapWrite, unmappt spätestens im Drop
+struct Mapping<'q, T: Pod, B: Backend, A: Access> {
+    buffer: Option<GpuBuffer<T, Mapped, B, A>>,
+    queue: &'q B::Queue,
+    ptr: *mut T,
+    write: bool,
+}
+
+impl<'q, T: Pod, B: Backend, A: Access> Mapping<'q, T, B, A> {
+    fn new(
+        mut buffer: GpuBuffer<T, Ready, B, A>,
+        queue: &'q B::Queue,
+        write: bool,
+    ) -> Result<Self, EnqueueError<GpuBuffer<T, Ready, B, A>>> {
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        // SAFETY: der Buffer steckt bis zum Unmap im Mapping, kein anderes Kommando sieht ihn
+        let ptr = match unsafe { B::map(queue, &mut buffer.buf, buffer.len, write) } {
+            Ok(ptr) => ptr,
+            Err(e)  => {
+                let err = e.context("map", Some(buffer.len * mem::size_of::<T>()));
+                return Err(EnqueueError::new(buffer, err));
+            }
+        };
+
+        #[cfg(feature="metrics")]
+        record("map", t);
+
+        Ok(Self { buffer: Some(buffer.into_state()), queue, ptr, write })
+    }
+
+    fn len(&self) -> usize { self.buffer.as_ref().map_or(0, |b| b.len) }
+
+    fn unmap(mut self) -> Unmapped<T, B, A> {
+        let mut buffer = self.buffer.take().expect("mapped until unmap or drop");
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        // SAFETY: `ptr` stammt aus `map`; die Sicht ist nur über `self` erreichbar, das hier endet
+        let done = unsafe { B::unmap(self.queue, &mut buffer.buf, self.ptr, self.write) }
+            .and_then(|evt| B::wait(&evt));
+        if let Err(e) = done {
+            return Err(EnqueueError::new(buffer.into_state(), e.context("unmap", None)));
+        }
+
+        #[cfg(feature="metrics")]
+        record("unmap", t);
+
+        Ok(buffer.into_state())
+    }
+}
+
+impl<T: Pod, B: Backend, A: Access> Drop for Mapping<'_, T, B, A> {
+    fn drop(&mut self) {
+        if let Some(mut buffer) = self.buffer.take() {
+            // SAFETY: wie in `unmap`; Fehler können im Drop nur verworfen werden
+            if let Ok(evt) = unsafe { B::unmap(self.queue, &mut buffer.buf, self.ptr, self.write) } {
+                let _ = B::wait(&evt);
+            }
+        }
+    }
+}
+
+/// Read-only host view of a mapped buffer; derefs to `&[T]`.
+/// [`unmap`](Self::unmap) returns the buffer as Ready, dropping the view
+/// unmaps and releases it.
+pub struct MapRead<'q, T: Pod, B: Backend = OpenCl, A: Access = ReadWrite>(Mapping<'q, T, B, A>);
+
+impl<T: Pod, B: Backend, A: Access> MapRead<'_, T, B, A> {
+    pub fn unmap(self) -> Unmapped<T, B, A> { self.0.unmap() }
+}
+
+impl<T: Pod, B: Backend, A: Access> Deref for MapRead<'_, T, B, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: `ptr` zeigt auf `len` gemappte, für `T` ausgerichtete Elemente
+        unsafe { slice::from_raw_parts(self.0.ptr, self.0.len()) }
+    }
+}
+
+/// Read-write host view of a mapped buffer; derefs to `&mut [T]`.
+/// [`unmap`](Self::unmap) writes back and returns the buffer as Ready,
+/// dropping the view writes back and releases it.
+pub struct MapWrite<'q, T: Pod, B: Backend = OpenCl, A: Access = ReadWrite>(Mapping<'q, T, B, A>);
+
+impl<T: Pod, B: Backend, A: Access> MapWrite<'_, T, B, A> {
+    pub fn unmap(self) -> Unmapped<T, B, A> { self.0.unmap() }
+}
+
+impl<T: Pod, B: Backend, A: Access> Deref for MapWrite<'_, T, B, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: siehe MapRead
+        unsafe { slice::from_raw_parts(self.0.ptr, self.0.len()) }
+    }
+}
+
+impl<T: Pod, B: Backend, A: Access> DerefMut for MapWrite<'_, T, B, A> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: wie oben; `&mut self` macht die Sicht exklusiv
+        unsafe { slice::from_raw_parts_mut(self.0.ptr, self.0.len()) }
+    }
+}
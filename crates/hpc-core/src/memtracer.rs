@@ -13,12 +13,14 @@ use std::{
 
 /// Transfer direction or kernel event
 #[derive(Clone, Copy)]
-pub enum Dir { H2D, D2H, Kernel }
+pub enum Dir { H2D, D2H, D2D, Fill, Kernel }
 impl Dir {
     fn as_str(self) -> &'static str {
         match self {
             Dir::H2D    => "H2D",
             Dir::D2H    => "D2H",
+            Dir::D2D    => "D2D",
+            Dir::Fill   => "Fill",
             Dir::Kernel => "Kernel",
         }
     }
@@ -27,8 +29,11 @@ impl Dir {
 /// global zero point – initialized on first start() call
 static T0: Lazy<Instant> = Lazy::new(Instant::now);
 
-/// Log buffer: (start, end, bytes, dir, idle)
-static LOG: Lazy<Mutex<Vec<(u128, u128, usize, &'static str, u128)>>> =
+/// Log line: (start, end, bytes, dir, idle, status)
+type Row = (u128, u128, usize, &'static str, u128, i32);
+
+/// Log buffer
+static LOG: Lazy<Mutex<Vec<Row>>> =
     Lazy::new(|| Mutex::new(Vec::new()));
 
 /// Global flag for automatic tracing
@@ -87,7 +92,11 @@ pub fn start(dir: Dir, bytes: usize) -> CopyToken {
 
 impl CopyToken {
     /// End of a transfer/kernel – writes a line with idle_us
-    pub fn finish(self) {
+    pub fn finish(self) { self.finish_with_status(0); }
+
+    /// Like `finish`, with the final execution status of the command
+    /// (`CL_COMPLETE` = 0 or a negative error code)
+    pub fn finish_with_status(self, status: i32) {
         let t0 = *T0;
         let s  = self.start.duration_since(t0).as_micros();
         let e  = Instant::now().duration_since(t0).as_micros();
@@ -95,17 +104,17 @@ impl CopyToken {
         // Idle time calculation
         let mut log = LOG.lock().unwrap();
         let prev_end = log.last().map(|entry| entry.1).unwrap_or(0);
-        let idle = if s > prev_end { s - prev_end } else { 0 };
+        let idle = s.saturating_sub(prev_end);
         
-        log.push((s, e, self.bytes, self.dir.as_str(), idle));
+        log.push((s, e, self.bytes, self.dir.as_str(), idle, status));
     }
 }
 
 /// Write CSV – call once at program end
 pub fn flush_csv() {
     let mut f = File::create("memtrace.csv").expect("konnte memtrace.csv nicht anlegen");
-    writeln!(f, "t_start_us,t_end_us,bytes,dir,idle_us").unwrap();
-    for (s, e, b, d, idle) in LOG.lock().unwrap().iter() {
-        writeln!(f, "{},{},{},{},{}", s, e, b, d, idle).unwrap();
+    writeln!(f, "t_start_us,t_end_us,bytes,dir,idle_us,status").unwrap();
+    for (s, e, b, d, idle, status) in LOG.lock().unwrap().iter() {
+        writeln!(f, "{},{},{},{},{},{}", s, e, b, d, idle, status).unwrap();
     }
 }
\ No newline at end of file
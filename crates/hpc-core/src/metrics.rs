@@ -26,6 +26,12 @@ pub fn record(name: &'static str, start: Instant) {
 pub static ALLOCS:      AtomicUsize = AtomicUsize::new(0);
 pub static ALLOC_BYTES: AtomicUsize = AtomicUsize::new(0);
 
+// BufferPool: Treffer, Neuallokationen, LRU-Verdrängungen
+
+pub static POOL_HITS:      AtomicUsize = AtomicUsize::new(0);
+pub static POOL_MISSES:    AtomicUsize = AtomicUsize::new(0);
+pub static POOL_EVICTIONS: AtomicUsize = AtomicUsize::new(0);
+
 
 pub fn summary() {
     // API‑Latenzen hrouping
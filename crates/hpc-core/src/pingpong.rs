@@ -0,0 +1,112 @@
+//! src/pingpong.rs
+//
+// Ping-Pong-Paar für iterative Stencils (Jacobi): zwei Ready-Buffer, pro
+// Schritt liest der Kernel `src` und schreibt `dst`, danach Rollentausch.
+// Ersetzt die frische Ziel-Allokation pro Iteration.
+
+use crate::{
+    launch, Backend, ClError, EnqueueError, GpuBuffer, GpuEventGuard, InFlight, InFlightSet, Input, NdRange, OpenCl,
+    Output, Ready,
+};
+use bytemuck::Pod;
+use opencl3::{command_queue::CommandQueue, kernel::Kernel, types::cl_uint};
+
+// (src, dst)
+type Pair<T, B> = (GpuBuffer<T, Ready, B>, GpuBuffer<T, Ready, B>);
+
+/// Result of the command enqueued by [`PingPong::step_with`]: source and
+/// destination in flight plus its guard (the shape of a device-to-device copy).
+pub type Step<T, B = OpenCl> = Result<
+    ((GpuBuffer<T, InFlight<'static>, B>, GpuBuffer<T, InFlight<'static>, B>), GpuEventGuard<'static, B>),
+    ClError,
+>;
+
+/// Two equally sized Ready buffers whose roles alternate every step:
+/// the kernel reads [`src`](Self::src) and writes [`dst`](Self::dst), then
+/// the result becomes the next source. Both stay owned by the pair, so a
+/// step can never bind the same buffer as input and output.
+pub struct PingPong<T: Pod, B: Backend = OpenCl> {
+    src: GpuBuffer<T, Ready, B>,
+    dst: GpuBuffer<T, Ready, B>,
+}
+
+impl<T: Pod, B: Backend> PingPong<T, B> {
+    /// `src` holds the initial state; `dst` must be initialised too if the
+    /// kernel leaves parts of it untouched (e.g. the boundary of a stencil).
+    pub fn new(
+        src: GpuBuffer<T, Ready, B>,
+        dst: GpuBuffer<T, Ready, B>,
+    ) -> Result<Self, EnqueueError<Pair<T, B>>> {
+        if dst.len != src.len {
+            let err = ClError::SizeMismatch { expected: src.len, actual: dst.len };
+            return Err(EnqueueError::new((src, dst), err));
+        }
+        Ok(Self { src, dst })
+    }
+
+    /// Input of the next step (the latest result).
+    pub fn src(&self) -> &GpuBuffer<T, Ready, B> { &self.src }
+
+    /// Output of the next step.
+    pub fn dst(&self) -> &GpuBuffer<T, Ready, B> { &self.dst }
+
+    pub fn len(&self) -> usize { self.src.len }
+
+    pub fn is_empty(&self) -> bool { self.src.len == 0 }
+
+    /// One step on any backend: `f` enqueues a command that reads its first
+    /// and writes its second argument. Blocks until it has completed, then
+    /// swaps the roles; a failed command is returned as error, as are
+    /// buffers of another length than the pair's ([`ClError::InvalidArgs`]).
+    pub fn step_with<F>(self, f: F) -> Result<Self, ClError>
+    where
+        F: FnOnce(GpuBuffer<T, Ready, B>, GpuBuffer<T, Ready, B>) -> Step<T, B>,
+    {
+        let len = self.src.len;
+        let (pair, guard) = f(self.src, self.dst)?;
+        let (src, dst) = pair.into_ready_checked(guard)?;
+        // `f` kann beliebige Buffer zurückgeben, die Paar-Invariante gilt nur geprüft
+        if src.len != len || dst.len != len {
+            return Err(ClError::InvalidArgs { reason: "step_with returned buffers of a different length" });
+        }
+        Ok(Self::swapped((src, dst)))
+    }
+
+    // Ergebnis (dst) wird Quelle des nächsten Schritts
+    fn swapped((src, dst): Pair<T, B>) -> Self { Self { src: dst, dst: src } }
+
+    /// `(src, dst)` of the next step.
+    pub fn into_parts(self) -> Pair<T, B> { (self.src, self.dst) }
+}
+
+impl<T: Pod> PingPong<T> {
+
+    /// Launches `kernel` with `src` bound as [`Input`] to argument `args[0]`
+    /// and `dst` as [`Output`] to `args[1]`, waits for it and swaps.
+    /// Scalar arguments are set beforehand with `kernel.set_arg`. If the
+    /// kernel fails, the pair comes back unswapped with the error: `src`
+    /// still holds the last result, the contents of `dst` are undefined.
+    pub fn step(
+        self,
+        queue: &CommandQueue,
+        kernel: &Kernel,
+        range: &NdRange,
+        args: [cl_uint; 2],
+    ) -> Result<Self, EnqueueError<Self>> {
+        match launch(queue, kernel, range, (Input(args[0], self.src), Output(args[1], self.dst))) {
+            // wie into_ready_checked, aber das Paar bleibt beim Fehler erhalten
+            Ok((pair, guard)) => {
+                let checked = guard.wait();
+                let (src, dst) = pair.relabel();
+                match checked {
+                    Ok(())     => Ok(Self::swapped((src, dst))),
+                    Err(error) => Err(EnqueueError::new(Self { src, dst }, error)),
+                }
+            }
+            Err(e) => {
+                let ((Input(_, src), Output(_, dst)), error) = e.into_parts();
+                Err(EnqueueError::new(Self { src, dst }, error))
+            }
+        }
+    }
+}
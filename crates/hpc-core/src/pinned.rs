@@ -0,0 +1,89 @@
+//! src/pinned.rs
+//
+// Gepinnter Host-Speicher: CL_MEM_ALLOC_HOST_PTR-Buffer, dauerhaft gemappt.
+// DMA-fähig ohne Staging-Kopie im Treiber; Deref auf [T] → direkt in
+// enqueue_write / enqueue_read verwendbar.
+
+use crate::{Backend, ClError, OpenCl};
+use bytemuck::Pod;
+use opencl3::memory::{CL_MEM_ALLOC_HOST_PTR, CL_MEM_READ_WRITE};
+use std::{mem, ops::{Deref, DerefMut}, ptr, slice};
+
+#[cfg(feature = "metrics")]
+use crate::record;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Page-locked host memory for `len` elements of `T`, zero-initialised.
+/// Derefs to `[T]`, so it can be passed wherever a host slice is expected
+/// (`enqueue_write(queue, &pinned)`, `enqueue_read(queue, &mut pinned)`).
+/// The mapping lives on `queue` and is released on drop.
+pub struct PinnedHostBuffer<'q, T: Pod, B: Backend = OpenCl> {
+    mem: B::Mem<T>,
+    queue: &'q B::Queue,
+    ptr: *mut T,
+    len: usize,
+}
+
+impl<'q, T: Pod> PinnedHostBuffer<'q, T> {
+
+    /// Allocates and maps an OpenCL buffer with `CL_MEM_ALLOC_HOST_PTR`.
+    pub fn new(
+        ctx: &opencl3::context::Context,
+        queue: &'q opencl3::command_queue::CommandQueue,
+        len: usize,
+    ) -> Result<Self, ClError> {
+        Self::new_in(ctx, queue, len)
+    }
+}
+
+impl<'q, T: Pod, B: Backend> PinnedHostBuffer<'q, T, B> {
+
+    /// Allocates pinned memory on backend `B` and maps it through `queue`.
+    pub fn new_in(ctx: &B::Context, queue: &'q B::Queue, len: usize) -> Result<Self, ClError> {
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let bytes = len * mem::size_of::<T>();
+        let mut mem = B::alloc::<T>(ctx, len, CL_MEM_READ_WRITE | CL_MEM_ALLOC_HOST_PTR)
+            .map_err(|e| e.context("PinnedHostBuffer::new", Some(bytes)))?;
+        // SAFETY: `mem` ist privat und wird nie in ein Kommando gegeben
+        let ptr = unsafe { B::map(queue, &mut mem, len, true) }
+            .map_err(|e| e.context("PinnedHostBuffer::new", Some(bytes)))?;
+        // Inhalt von ALLOC_HOST_PTR-Speicher ist undefiniert
+        // SAFETY: `ptr` zeigt auf `len` gemappte Elemente
+        unsafe { ptr::write_bytes(ptr, 0, len) };
+
+        #[cfg(feature="metrics")]
+        record("PinnedHostBuffer::new", t);
+
+        Ok(Self { mem, queue, ptr, len })
+    }
+}
+
+impl<T: Pod, B: Backend> Deref for PinnedHostBuffer<'_, T, B> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: Mapping bleibt bis zum Drop gültig
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<T: Pod, B: Backend> DerefMut for PinnedHostBuffer<'_, T, B> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: wie oben, `&mut self` ist exklusiv
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<T: Pod, B: Backend> Drop for PinnedHostBuffer<'_, T, B> {
+    fn drop(&mut self) {
+        // Laufende Transfers leihen `self` über ihren Guard → hier sind alle fertig
+        // SAFETY: `ptr` stammt aus `map` und wird danach nicht mehr benutzt
+        if let Ok(evt) = unsafe { B::unmap(self.queue, &mut self.mem, self.ptr, true) } {
+            let _ = B::wait(&evt);
+        }
+    }
+}
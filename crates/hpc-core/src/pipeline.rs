@@ -0,0 +1,251 @@
+//! src/pipeline.rs
+//
+// Out-of-Core-Schleife: Host-Daten in Chunks durch H2D → Kernel → D2H,
+// mit `depth` Buffer-Paaren im Flug. Die Reihenfolge zwischen den Queues
+// regelt das StreamPair, hier wird nur in der richtigen Folge eingereiht.
+// Transfers laufen über eigene Staging-Puffer statt direkt aus `input` /
+// in `output`: so darf ein Timeout zurückkehren, solange sie noch laufen.
+
+use crate::{Backend, ClError, GpuBuffer, GpuEventGuard, OpenCl, Paired, Queued, StreamPair};
+use bytemuck::Pod;
+use std::{collections::VecDeque, mem, ops::Range, slice, time::{Duration, Instant}};
+
+#[cfg(feature = "metrics")]
+use crate::record;
+
+/// Result of the kernel stage of a [`Pipeline`]: input and output buffer
+/// of the chunk plus the guard of the (last) compute command.
+pub type Stage<T, U, B = OpenCl> =
+    Result<((GpuBuffer<T, Paired, B>, GpuBuffer<U, Paired, B>), GpuEventGuard<'static, B>), ClError>;
+
+/// Chunked, multi-buffered streaming of a host slice through a kernel.
+///
+/// While the kernel works on chunk `i`, the transfer queue uploads chunk
+/// `i + 1` and downloads chunk `i - 1`. Each stage is traced by memtrace
+/// (H2D / kernel / D2H per chunk) when auto-tracing is on. Chunks pass
+/// through host staging buffers of the pipeline, the device never accesses
+/// `input` or `output` directly.
+#[derive(Clone, Copy, Debug)]
+pub struct Pipeline {
+    chunk: usize,
+    depth: usize,
+    timeout: Option<Duration>,
+}
+
+impl Pipeline {
+    /// `chunk` elements per stage, double-buffered. Panics if `chunk` is 0.
+    pub fn new(chunk: usize) -> Self {
+        assert!(chunk > 0, "chunk size must be positive");
+        Self { chunk, depth: 2, timeout: None }
+    }
+
+    /// Number of input/output buffer pairs in flight (default 2, min 1).
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Bounds every wait of a run to `timeout`: reusing a buffer pair and
+    /// draining the outstanding commands at the end. On expiry the run
+    /// fails with [`ClError::Timeout`]; commands still running then finish
+    /// into staging memory that is leaked, never into `output`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Streams `input` through `kernel` into `output` (same length).
+    ///
+    /// `kernel(streams, len, input, output)` gets the device buffers of one
+    /// chunk as [`Paired`], of which the first `len` elements are valid, and
+    /// enqueues the compute work, typically via [`StreamPair::launch`]. Blocks until the
+    /// last chunk has been downloaded; a failed command of any stage is
+    /// returned as [`ClError::CommandFailed`].
+    pub fn run<T, U, B, F>(
+        &self,
+        streams: &StreamPair<B>,
+        ctx: &B::Context,
+        input: &[T],
+        output: &mut [U],
+        kernel: F,
+    ) -> Result<(), ClError>
+    where
+        T: Pod,
+        U: Pod,
+        B: Backend,
+        F: FnMut(&StreamPair<B>, usize, GpuBuffer<T, Paired, B>, GpuBuffer<U, Paired, B>) -> Stage<T, U, B>,
+    {
+        self.run_until(streams, ctx, input, output, || false, kernel).map(|_| ())
+    }
+
+    /// Like [`run`](Self::run), but asks `stop` before every chunk. Once it
+    /// returns `true` the pipeline is abandoned: nothing further is enqueued,
+    /// the outstanding commands are drained (within the
+    /// [`timeout`](Self::timeout), if set) and the number of elements
+    /// written to `output` (always a prefix) is returned. `stop` may watch
+    /// a flag, a deadline or a signal handler.
+    pub fn run_until<T, U, B, C, F>(
+        &self,
+        streams: &StreamPair<B>,
+        ctx: &B::Context,
+        input: &[T],
+        output: &mut [U],
+        mut stop: C,
+        mut kernel: F,
+    ) -> Result<usize, ClError>
+    where
+        T: Pod,
+        U: Pod,
+        B: Backend,
+        C: FnMut() -> bool,
+        F: FnMut(&StreamPair<B>, usize, GpuBuffer<T, Paired, B>, GpuBuffer<U, Paired, B>) -> Stage<T, U, B>,
+    {
+        if output.len() != input.len() {
+            return Err(ClError::SizeMismatch { expected: input.len(), actual: output.len() });
+        }
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let chunk = self.chunk.min(input.len().max(1));
+        let slots = self.depth.min(input.len().div_ceil(chunk));
+        let mut staging = Staging::<T, U, B>::new(slots, chunk);
+        let mut free = VecDeque::new();
+        for slot in 0..slots {
+            let (buf_in, buf_out) = (GpuBuffer::<T, Queued, B>::new_in(ctx, chunk)?, GpuBuffer::<U, Queued, B>::new_in(ctx, chunk)?);
+            free.push_back((slot, streams.adopt(buf_in), streams.adopt(buf_out)));
+        }
+        let deadline = |timeout: Duration| Instant::now() + timeout;
+        let mut pending = VecDeque::new();
+        let mut inputs = input.chunks(chunk);
+        let mut done = 0;
+
+        while !stop() {
+            // Uploads vorziehen, solange Buffer frei sind: H2D(i+1) steht so
+            // vor D2H(i) in der Transfer-Queue und läuft parallel zu Kernel(i)
+            while !free.is_empty() {
+                let Some(host) = inputs.next() else { break };
+                let (slot, buf_in, buf_out) = free.pop_front().expect("checked above");
+                // Vorlauf begrenzt: der vorige Chunk des Slots muss durch sein
+                staging.settle(slot, output, self.timeout.map(deadline))?;
+                // SAFETY: keine Kommandos mehr auf dem Slot (settle)
+                let stage = unsafe { staging.input(slot, host.len()) };
+                stage.copy_from_slice(host);
+                // SAFETY: Staging lebt bis alle Guards fertig sind oder wird geleakt
+                let (buf_in, guard) = unsafe { streams.write_at(buf_in, 0, stage) }?;
+                staging.guards[slot].push(guard);
+                pending.push_back((slot, host.len(), buf_in, buf_out));
+            }
+            let Some((slot, len, buf_in, buf_out)) = pending.pop_front() else { break };
+
+            let ((buf_in, buf_out), guard) = kernel(streams, len, buf_in, buf_out)?;
+            staging.guards[slot].push(guard);
+
+            // SAFETY: wie beim Upload; settle kopiert erst nach dem Download
+            let (buf_out, guard) = unsafe { streams.read_range(buf_out, 0..len, staging.output(slot, len)) }?;
+            staging.guards[slot].push(guard);
+            staging.targets[slot] = Some(done..done + len);
+            done += len;
+            free.push_back((slot, buf_in, buf_out));
+        }
+        // Abbruch oder Ende: laufende Kommandos abwarten, fehlgeschlagene
+        // Stufen melden statt Ergebnis stillschweigend zu übernehmen
+        let drain = self.timeout.map(deadline);
+        for slot in 0..slots {
+            staging.settle(slot, output, drain)?;
+        }
+        // Buffer aus dem Paar lösen, sonst wächst dessen ID-Menge mit jedem Lauf
+        let pending = pending.into_iter().map(|(slot, _, buf_in, buf_out)| (slot, buf_in, buf_out));
+        for (_, buf_in, buf_out) in free.into_iter().chain(pending) {
+            let _ = (streams.into_ready(buf_in), streams.into_ready(buf_out));
+        }
+
+        #[cfg(feature="metrics")]
+        record("pipeline", t);
+
+        Ok(done)
+    }
+}
+
+// Host-Zwischenpuffer, je `chunk` Elemente pro Slot (Buffer-Paar).
+// Nach einem Timeout können Transfers noch darauf zugreifen: dann werden
+// Speicher und Guards geleakt statt freigegeben bzw. blockierend gewartet
+struct Staging<T: Pod, U: Pod, B: Backend> {
+    input: *mut [T],
+    output: *mut [U],
+    chunk: usize,
+    // Guards der Kommandos des letzten Chunks je Slot, in Einreihungsfolge
+    guards: Vec<Vec<GpuEventGuard<'static, B>>>,
+    // Ziel des letzten Downloads je Slot in `output`
+    targets: Vec<Option<Range<usize>>>,
+    timed_out: bool,
+}
+
+impl<T: Pod, U: Pod, B: Backend> Staging<T, U, B> {
+    fn new(slots: usize, chunk: usize) -> Self {
+        Self {
+            input: Box::into_raw(vec![T::zeroed(); slots * chunk].into_boxed_slice()),
+            output: Box::into_raw(vec![U::zeroed(); slots * chunk].into_boxed_slice()),
+            chunk,
+            guards: (0..slots).map(|_| Vec::new()).collect(),
+            targets: vec![None; slots],
+            timed_out: false,
+        }
+    }
+
+    // Eingabe-Bereich des Slots; ohne Referenz auf den ganzen Puffer, andere
+    // Slots können gerade übertragen werden.
+    // SAFETY (Aufrufer): kein Kommando liest den Bereich mehr, solange die
+    // Referenz lebt; für Transfers gilt das bis zum Ende ihres Guards
+    unsafe fn input<'s>(&self, slot: usize, len: usize) -> &'s mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.input.cast::<T>().add(slot * self.chunk), len) }
+    }
+
+    // SAFETY (Aufrufer): wie bei `input`
+    unsafe fn output<'s>(&self, slot: usize, len: usize) -> &'s mut [U] {
+        unsafe { slice::from_raw_parts_mut(self.output.cast::<U>().add(slot * self.chunk), len) }
+    }
+
+    // Kommandos des Slots abwarten, höchstens bis `deadline`, und seinen
+    // letzten Download nach `output` kopieren
+    fn settle(&mut self, slot: usize, output: &mut [U], deadline: Option<Instant>) -> Result<(), ClError> {
+        while let Some(guard) = self.guards[slot].first_mut() {
+            let done = match deadline {
+                Some(deadline) => guard.wait_timeout(deadline.saturating_duration_since(Instant::now())),
+                None           => Ok(true),
+            };
+            match done {
+                Ok(true)   => self.guards[slot].remove(0).wait()?,
+                Ok(false)  => {
+                    self.timed_out = true;
+                    return Err(ClError::Timeout);
+                }
+                Err(error) => {
+                    self.guards[slot].remove(0).dismiss();
+                    return Err(error);
+                }
+            }
+        }
+        if let Some(target) = self.targets[slot].take() {
+            // SAFETY: alle Kommandos des Slots sind beendet
+            output[target.clone()].copy_from_slice(unsafe { self.output(slot, target.len()) });
+        }
+        Ok(())
+    }
+}
+
+impl<T: Pod, U: Pod, B: Backend> Drop for Staging<T, U, B> {
+    fn drop(&mut self) {
+        if self.timed_out {
+            self.guards.drain(..).flatten().for_each(mem::forget);
+            return;
+        }
+        // Drop der Guards wartet auf die Transfers
+        self.guards.clear();
+        // SAFETY: aus `Box::into_raw`, kein Kommando greift mehr zu
+        unsafe {
+            drop(Box::from_raw(self.input));
+            drop(Box::from_raw(self.output));
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! src/pool.rs
+//
+// Buffer-Pool: Device-Speicher wiederverwenden statt clCreateBuffer pro
+// Iteration. Größenklassen (Default: Zweierpotenzen), Byte-Budget mit
+// LRU-Verdrängung, Rückgabe beim Drop des Pooled-Handles.
+
+use crate::{
+    keep_alive, next_id, sealed::Sealed, Backend, ClError, DeviceAlloc, EnqueueError, GpuBuffer, InFlight, InFlightSet,
+    KernelArgs, KernelRead, KernelWrite, MemKey, OpenCl, Paired, Queued, Ready, ReadWrite, State, Unpaired, Writable,
+};
+use bytemuck::Pod;
+use opencl3::{error_codes::CL_INVALID_BUFFER_SIZE, event::Event, kernel::Kernel, memory::ClMem, types::cl_uint};
+use std::{cell::RefCell, collections::VecDeque, marker::PhantomData, mem, ops::Deref};
+
+#[cfg(feature = "metrics")]
+use crate::{POOL_EVICTIONS, POOL_HITS, POOL_MISSES};
+#[cfg(feature = "metrics")]
+use std::sync::atomic::Ordering;
+
+// Ergebnis von Pooled::then: neuer Zustand + Rückgabe oder altes Handle
+type Then<'p, T, S, S2, R, B, A> = Result<(Pooled<'p, T, S2, B, A>, R), EnqueueError<Pooled<'p, T, S, B, A>>>;
+
+/// Rounding of requested lengths to the cached allocation sizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeClass {
+    /// Next power of two (default); wastes at most half of an allocation.
+    PowerOfTwo,
+    /// Next multiple of the given number of elements.
+    Multiple(usize),
+    /// Only requests of exactly the same length share allocations.
+    Exact,
+}
+
+impl SizeClass {
+    /// Allocated length for a request of `len` elements, `None` if it
+    /// does not fit in `usize`.
+    pub fn round(self, len: usize) -> Option<usize> {
+        match self {
+            SizeClass::PowerOfTwo  => len.checked_next_power_of_two(),
+            SizeClass::Multiple(n) => len.div_ceil(n.max(1)).checked_mul(n.max(1)),
+            SizeClass::Exact       => Some(len),
+        }
+    }
+}
+
+/// Cache of device allocations for buffers of `T` with the flags of `A`.
+///
+/// [`get`](Self::get) rounds the length up to its [`SizeClass`] and reuses
+/// the most recently returned allocation of that class, or allocates a new
+/// one. Dropping the [`Pooled`] handle returns the allocation; beyond
+/// `max_bytes` of cached memory the least recently used ones are released.
+pub struct BufferPool<'c, T: Pod, B: Backend = OpenCl, A: DeviceAlloc = ReadWrite> {
+    ctx: &'c B::Context,
+    class: SizeClass,
+    max_bytes: usize,
+    // freie Allokationen (Kapazität, Speicher), vorne = am längsten unbenutzt
+    free: RefCell<VecDeque<(usize, B::Mem<T>)>>,
+    _access: PhantomData<A>,
+}
+
+impl<'c, T: Pod, B: Backend, A: DeviceAlloc> BufferPool<'c, T, B, A> {
+    /// Pool with power-of-two size classes caching up to `max_bytes`.
+    pub fn new(ctx: &'c B::Context, max_bytes: usize) -> Self {
+        Self { ctx, class: SizeClass::PowerOfTwo, max_bytes, free: RefCell::new(VecDeque::new()), _access: PhantomData }
+    }
+
+    pub fn size_class(mut self, class: SizeClass) -> Self {
+        self.class = class;
+        self
+    }
+
+    /// Buffer of `len` elements, backed by an allocation of
+    /// `size_class().round(len)` elements; a length that cannot be rounded
+    /// fails with `CL_INVALID_BUFFER_SIZE`.
+    pub fn get(&self, len: usize) -> Result<Pooled<'_, T, Queued, B, A>, ClError> {
+        let capacity = self.class.round(len)
+            .ok_or_else(|| ClError::from(CL_INVALID_BUFFER_SIZE).context("BufferPool::get", None))?;
+        // zuletzt zurückgegebene Allokation der Klasse zuerst (LRU-Ende)
+        let cached = {
+            let mut free = self.free.borrow_mut();
+            free.iter().rposition(|(cap, _)| *cap == capacity).and_then(|i| free.remove(i))
+        };
+
+        #[cfg(feature = "metrics")]
+        match cached {
+            Some(_) => POOL_HITS.fetch_add(1, Ordering::Relaxed),
+            None    => POOL_MISSES.fetch_add(1, Ordering::Relaxed),
+        };
+
+        let buf = match cached {
+            Some((_, mem)) => mem,
+            None => GpuBuffer::<T, Queued, B, A>::new_in(self.ctx, capacity)?.buf,
+        };
+        // `len` ≤ Kapazität: alle Operationen prüfen gegen `len`
+        let buf = GpuBuffer { buf, len, id: next_id(), parent: 0, _state: PhantomData };
+        Ok(Pooled { buf: Some(buf), capacity, pool: self })
+    }
+
+    /// Bytes currently cached (not handed out).
+    pub fn cached_bytes(&self) -> usize {
+        self.free.borrow().iter().map(|(cap, _)| cap * mem::size_of::<T>()).sum()
+    }
+
+    /// Releases every cached allocation.
+    pub fn clear(&self) { self.free.borrow_mut().clear(); }
+
+    // Allokation zurücknehmen, über dem Budget die ältesten freigeben
+    fn put(&self, capacity: usize, mem: B::Mem<T>) {
+        // passt allein nicht ins Budget → direkt freigeben, Cache bleibt
+        if capacity * mem::size_of::<T>() > self.max_bytes {
+            drop(mem);
+
+            #[cfg(feature = "metrics")]
+            POOL_EVICTIONS.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let mut free = self.free.borrow_mut();
+        free.push_back((capacity, mem));
+        let mut cached: usize = free.iter().map(|(cap, _)| cap * mem::size_of::<T>()).sum();
+        while cached > self.max_bytes {
+            let Some((cap, _evicted)) = free.pop_front() else { break };
+            cached -= cap * mem::size_of::<T>();
+
+            #[cfg(feature = "metrics")]
+            POOL_EVICTIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Buffer on loan from a [`BufferPool`]; derefs to the [`GpuBuffer`].
+///
+/// State changes go through [`then`](Self::then) (enqueue operations) and
+/// [`map`](Self::map) (e.g. `into_ready`), in-flight handles also complete
+/// via [`InFlightSet::into_ready`]. Dropping the handle returns the
+/// allocation to the pool, unless a command may still use it (`InFlight`).
+pub struct Pooled<'p, T: Pod, S: State, B: Backend = OpenCl, A: DeviceAlloc = ReadWrite> {
+    buf: Option<GpuBuffer<T, S, B, A>>,
+    capacity: usize,
+    pool: &'p BufferPool<'p, T, B, A>,
+}
+
+impl<'p, T: Pod, S: State, B: Backend, A: DeviceAlloc> Pooled<'p, T, S, B, A> {
+
+    /// Applies an infallible state change, e.g. `|b| b.into_ready(guard)`.
+    pub fn map<S2: State>(mut self, f: impl FnOnce(GpuBuffer<T, S, B, A>) -> GpuBuffer<T, S2, B, A>) -> Pooled<'p, T, S2, B, A> {
+        let buf = f(self.take());
+        Pooled { buf: Some(buf), capacity: self.capacity, pool: self.pool }
+    }
+
+    /// Runs an enqueue operation such as `|b| b.enqueue_fill(queue, 0)`;
+    /// the buffer stays pooled in its new state. On failure the handle
+    /// comes back unchanged.
+    pub fn then<S2: State, R>(
+        mut self,
+        f: impl FnOnce(GpuBuffer<T, S, B, A>) -> Result<(GpuBuffer<T, S2, B, A>, R), EnqueueError<GpuBuffer<T, S, B, A>>>,
+    ) -> Then<'p, T, S, S2, R, B, A> {
+        match f(self.take()) {
+            Ok((buf, out)) => Ok((Pooled { buf: Some(buf), capacity: self.capacity, pool: self.pool }, out)),
+            Err(e) => {
+                let (buf, error) = e.into_parts();
+                self.buf = Some(buf);
+                Err(EnqueueError::new(self, error))
+            }
+        }
+    }
+
+    /// Detaches the buffer from the pool; its allocation is released on
+    /// drop like any other buffer.
+    pub fn into_inner(mut self) -> GpuBuffer<T, S, B, A> { self.take() }
+
+    // Buffer ist bis zum Drop / into_inner immer vorhanden
+    fn take(&mut self) -> GpuBuffer<T, S, B, A> {
+        self.buf.take().expect("pooled buffer present until drop")
+    }
+}
+
+impl<T: Pod, S: State, B: Backend, A: DeviceAlloc> Deref for Pooled<'_, T, S, B, A> {
+    type Target = GpuBuffer<T, S, B, A>;
+
+    fn deref(&self) -> &Self::Target { self.buf.as_ref().expect("pooled buffer present until drop") }
+}
+
+impl<T: Pod, S: State, B: Backend, A: DeviceAlloc> Drop for Pooled<'_, T, S, B, A> {
+    fn drop(&mut self) {
+        // laufendes Kommando → Speicher normal freigeben statt wiederverwenden
+        if let Some(buf) = self.buf.take() && !S::PENDING {
+            self.pool.put(self.capacity, buf.buf);
+        }
+    }
+}
+
+impl<T: Pod, S: State, B: Backend, A: DeviceAlloc> Sealed for Pooled<'_, T, S, B, A> {}
+impl<'a, 'p, T: Pod, B: Backend, A: DeviceAlloc> InFlightSet<'a, B> for Pooled<'p, T, InFlight<'a>, B, A> {
+    type Ready = Pooled<'p, T, Ready, B, A>;
+    type Paired = Pooled<'p, T, Paired, B, A>;
+
+    fn relabel(self) -> Self::Ready { self.map(GpuBuffer::into_state) }
+
+    fn pair(self) -> Self::Paired { self.map(GpuBuffer::into_state) }
+}
+
+/// [`Input`](crate::Input) for a pooled buffer.
+pub struct PooledInput<'p, T: Pod, A: KernelRead + DeviceAlloc = ReadWrite>(pub cl_uint, pub Pooled<'p, T, Ready, OpenCl, A>);
+
+/// [`Output`](crate::Output) for a pooled buffer.
+pub struct PooledOutput<'p, T: Pod, S: Writable = Ready, A: KernelWrite + DeviceAlloc = ReadWrite>(
+    pub cl_uint,
+    pub Pooled<'p, T, S, OpenCl, A>,
+);
+
+impl<T: Pod, A: KernelRead + DeviceAlloc> Sealed for PooledInput<'_, T, A> {}
+impl<'p, T: Pod, A: KernelRead + DeviceAlloc> KernelArgs for PooledInput<'p, T, A> {
+    type InFlight<'a> = Pooled<'p, T, InFlight<'a>, OpenCl, A> where Self: 'a;
+
+    const HOST_PTR: bool = A::HOST_PTR;
+
+    fn bind(&self, kernel: &Kernel) -> Result<(), ClError> {
+        kernel.set_arg(self.0, &(*self.1).buf.get())?;
+        Ok(())
+    }
+
+    fn into_in_flight<'a>(self) -> Self::InFlight<'a> where Self: 'a { self.1.map(GpuBuffer::into_state) }
+
+    fn mem_ids(&self, ids: &mut Vec<MemKey>) { ids.push((*self.1).mem_key()); }
+
+    fn keep_alive(&self, evt: &Event) { keep_alive::<T, OpenCl>(&(*self.1).buf, evt); }
+}
+
+impl<T: Pod, A: KernelRead + DeviceAlloc> Unpaired for PooledInput<'_, T, A> {}
+
+impl<T: Pod, S: Writable, A: KernelWrite + DeviceAlloc> Sealed for PooledOutput<'_, T, S, A> {}
+impl<'p, T: Pod, S: Writable, A: KernelWrite + DeviceAlloc> KernelArgs for PooledOutput<'p, T, S, A> {
+    type InFlight<'a> = Pooled<'p, T, InFlight<'a>, OpenCl, A> where Self: 'a;
+
+    const HOST_PTR: bool = A::HOST_PTR;
+
+    fn bind(&self, kernel: &Kernel) -> Result<(), ClError> {
+        kernel.set_arg(self.0, &(*self.1).buf.get())?;
+        Ok(())
+    }
+
+    fn into_in_flight<'a>(self) -> Self::InFlight<'a> where Self: 'a { self.1.map(GpuBuffer::into_state) }
+
+    fn mem_ids(&self, ids: &mut Vec<MemKey>) { ids.push((*self.1).mem_key()); }
+
+    fn keep_alive(&self, evt: &Event) { keep_alive::<T, OpenCl>(&(*self.1).buf, evt); }
+}
+
+impl<T: Pod, S: Writable, A: KernelWrite + DeviceAlloc> Unpaired for PooledOutput<'_, T, S, A> {}
@@ -0,0 +1,263 @@
+//! src/rect.rs
+//
+// Rechteck-Transfers (2D/3D) über clEnqueue{Read,Write,Copy}BufferRect:
+// z.B. Halo-Spalten eines Stencil-Gitters ohne Gather auf dem Host.
+
+use crate::{
+    keep_alive, Access, Backend, ClError, Copied, EnqueueError, Enqueued, GpuBuffer, GpuEventGuard, HostRead, HostWrite, Ready,
+    Writable,
+};
+use bytemuck::Pod;
+use std::mem;
+
+#[cfg(feature = "memtrace")]
+use crate::{trace_begin, trace_on_complete, Dir};
+
+#[cfg(feature = "metrics")]
+use crate::record;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Box within a row-major 1–3D layout, all values in elements of `T`:
+/// `origin` and `region` as `[x, y, z]`, `row_pitch` elements per row and
+/// `slice_pitch` elements per 2D slice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    origin: [usize; 3],
+    region: [usize; 3],
+    row_pitch: usize,
+    slice_pitch: usize,
+}
+
+impl Rect {
+    /// `region[0]`×`region[1]` box at `origin` of a grid with `row_pitch` columns.
+    pub fn d2(origin: [usize; 2], region: [usize; 2], row_pitch: usize) -> Self {
+        Self {
+            origin: [origin[0], origin[1], 0],
+            region: [region[0], region[1], 1],
+            row_pitch,
+            // saturiert: ein Überlauf fällt in `check` als InvalidRect auf
+            slice_pitch: row_pitch.saturating_mul(origin[1].saturating_add(region[1])),
+        }
+    }
+
+    pub fn d3(origin: [usize; 3], region: [usize; 3], row_pitch: usize, slice_pitch: usize) -> Self {
+        Self { origin, region, row_pitch, slice_pitch }
+    }
+
+    /// Same region at origin 0 with tight pitches, i.e. a contiguous host
+    /// slice of exactly [`count`](Self::count) elements.
+    pub fn packed(&self) -> Self {
+        let [x, y, z] = self.region;
+        Self { origin: [0; 3], region: [x, y, z], row_pitch: x, slice_pitch: x.saturating_mul(y) }
+    }
+
+    pub fn origin(&self) -> [usize; 3] { self.origin }
+
+    pub fn region(&self) -> [usize; 3] { self.region }
+
+    pub fn row_pitch(&self) -> usize { self.row_pitch }
+
+    pub fn slice_pitch(&self) -> usize { self.slice_pitch }
+
+    /// Number of elements in the region, saturating at `usize::MAX`.
+    pub fn count(&self) -> usize {
+        self.region.iter().try_fold(1usize, |n, &r| n.checked_mul(r)).unwrap_or(usize::MAX)
+    }
+
+    // Element-Index des ersten Elements jeder Zeile, Zeilen haben region[0] Elemente.
+    // Nach `check` überlauffrei, sonst saturiert (der Zugriff scheitert dann an `len`)
+    pub(crate) fn rows(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.region[2]).flat_map(move |dz| {
+            (0..self.region[1]).map(move |dy| self.index([0, dy, dz]).unwrap_or(usize::MAX))
+        })
+    }
+
+    // Element-Index von `origin + d`, None bei Überlauf
+    fn index(&self, [dx, dy, dz]: [usize; 3]) -> Option<usize> {
+        let [x, y, z] = self.origin;
+        let row = y.checked_add(dy)?.checked_mul(self.row_pitch)?;
+        z.checked_add(dz)?.checked_mul(self.slice_pitch)?.checked_add(row)?.checked_add(x.checked_add(dx)?)
+    }
+
+    // Pitch-Regeln von OpenCL + Bereich muss in `len` Elementen liegen.
+    // Überläufe (Release baut ohne overflow-checks) sind InvalidRect
+    fn check(&self, len: usize) -> Result<(), ClError> {
+        let invalid = || ClError::InvalidRect {
+            region: self.region,
+            row_pitch: self.row_pitch,
+            slice_pitch: self.slice_pitch,
+        };
+        let [x, y, z] = self.region;
+        // Slice-Pitch als Vielfaches des Zeilen-Pitch, wie vom Host-Layout erwartet
+        let pitches = x <= self.row_pitch
+            && y.checked_mul(self.row_pitch).is_some_and(|min| min <= self.slice_pitch)
+            && self.slice_pitch.is_multiple_of(self.row_pitch);
+        if x == 0 || y == 0 || z == 0 || !pitches {
+            return Err(invalid());
+        }
+        let start = self.index([0; 3]).ok_or_else(invalid)?;
+        let end = self.index([x, y - 1, z - 1]).ok_or_else(invalid)?;
+        match end <= len {
+            true  => Ok(()),
+            false => Err(ClError::OutOfRange { offset: start, count: end - start, len }),
+        }
+    }
+}
+
+// Quelle und Ziel müssen gültig sein und dieselbe Region haben
+fn check_pair(src: &Rect, src_len: usize, dst: &Rect, dst_len: usize) -> Result<(), ClError> {
+    src.check(src_len)?;
+    dst.check(dst_len)?;
+    match src.region == dst.region {
+        true  => Ok(()),
+        false => Err(ClError::RectMismatch { src: src.region, dst: dst.region }),
+    }
+}
+
+// Host → Device
+impl<T: Pod, S: Writable, B: Backend, A: HostWrite> GpuBuffer<T, S, B, A> {
+
+    /// Uploads the box `host_rect` of `host` into the box `rect` of the buffer.
+    /// Both boxes must have the same region.
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_write`](GpuBuffer::enqueue_write).
+    pub unsafe fn enqueue_write_rect<'a>(
+        mut self,
+        queue: &B::Queue,
+        rect: Rect,
+        host: &'a [T],
+        host_rect: Rect,
+    ) -> Enqueued<'a, T, S, B, A>
+    where
+        A: 'a,
+    {
+        if let Err(err) = check_pair(&host_rect, host.len(), &rect, self.len) {
+            return Err(EnqueueError::new(self, err));
+        }
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let bytes = rect.count() * mem::size_of::<T>();
+
+        #[cfg(feature="memtrace")]
+        let token_box = trace_begin(Dir::H2D, bytes);
+
+        // SAFETY: `host` bleibt über `'a` im Guard geliehen, Drop wartet auf das
+        // Event; der Aufrufer garantiert, dass der Guard nicht geleakt wird
+        let evt = match unsafe { B::write_rect(queue, &mut self.buf, &rect, host, &host_rect, &[]) } {
+            Ok(evt) => evt,
+            Err(e)  => return Err(EnqueueError::new(self, e.context("enqueue_write_rect", Some(bytes)))),
+        };
+
+        #[cfg(feature="memtrace")]
+        trace_on_complete::<B>(&evt, token_box);
+
+        #[cfg(feature="metrics")]
+        record("enqueue_write_rect", t);
+
+        keep_alive::<T, B>(&self.buf, &evt);
+        let ids = [self.id];
+        Ok((self.into_state(), GpuEventGuard::new(evt, true, &ids)))
+    }
+}
+
+// Device → Host
+impl<T: Pod, B: Backend, A: HostRead> GpuBuffer<T, Ready, B, A> {
+
+    /// Downloads the box `rect` into the box `host_rect` of `host_out`;
+    /// elements of `host_out` outside `host_rect` stay untouched.
+    ///
+    /// # Safety
+    ///
+    /// As for [`enqueue_read`](GpuBuffer::enqueue_read).
+    pub unsafe fn enqueue_read_rect<'a>(
+        self,
+        queue: &B::Queue,
+        rect: Rect,
+        host_out: &'a mut [T],
+        host_rect: Rect,
+    ) -> Enqueued<'a, T, Ready, B, A>
+    where
+        A: 'a,
+    {
+        if let Err(err) = check_pair(&rect, self.len, &host_rect, host_out.len()) {
+            return Err(EnqueueError::new(self, err));
+        }
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let bytes = rect.count() * mem::size_of::<T>();
+
+        #[cfg(feature="memtrace")]
+        let token_box = trace_begin(Dir::D2H, bytes);
+
+        // SAFETY: `host_out` bleibt über `'a` im Guard geliehen, Drop wartet auf
+        // das Event; der Aufrufer garantiert, dass der Guard nicht geleakt wird
+        let evt = match unsafe { B::read_rect(queue, &self.buf, &rect, host_out, &host_rect, &[]) } {
+            Ok(evt) => evt,
+            Err(e)  => return Err(EnqueueError::new(self, e.context("enqueue_read_rect", Some(bytes)))),
+        };
+
+        #[cfg(feature="memtrace")]
+        trace_on_complete::<B>(&evt, token_box);
+
+        #[cfg(feature="metrics")]
+        record("enqueue_read_rect", t);
+
+        keep_alive::<T, B>(&self.buf, &evt);
+        let ids = [self.id];
+        Ok((self.into_state(), GpuEventGuard::new(evt, true, &ids)))
+    }
+}
+
+// Device → Device
+impl<T: Pod, B: Backend, A: Access> GpuBuffer<T, Ready, B, A> {
+
+    /// Copies the box `rect` into the box `dst_rect` of `dst`.
+    pub fn enqueue_copy_rect_to<'a, D: Writable, DA: Access + 'a>(
+        self,
+        queue: &B::Queue,
+        rect: Rect,
+        mut dst: GpuBuffer<T, D, B, DA>,
+        dst_rect: Rect,
+    ) -> Copied<'a, T, D, B, A, DA>
+    where
+        A: 'a,
+    {
+        if let Err(err) = check_pair(&rect, self.len, &dst_rect, dst.len) {
+            return Err(EnqueueError::new((self, dst), err));
+        }
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let bytes = rect.count() * mem::size_of::<T>();
+
+        #[cfg(feature="memtrace")]
+        let token_box = trace_begin(Dir::D2D, bytes);
+
+        let evt = match B::copy_rect(queue, &self.buf, &rect, &mut dst.buf, &dst_rect, &[]) {
+            Ok(evt) => evt,
+            Err(e)  => return Err(EnqueueError::new((self, dst), e.context("enqueue_copy_rect", Some(bytes)))),
+        };
+
+        #[cfg(feature="memtrace")]
+        trace_on_complete::<B>(&evt, token_box);
+
+        #[cfg(feature="metrics")]
+        record("enqueue_copy_rect", t);
+
+        keep_alive::<T, B>(&self.buf, &evt);
+        keep_alive::<T, B>(&dst.buf, &evt);
+        let ids = [self.id, dst.id];
+        Ok((
+            (self.into_state(), dst.into_state()),
+            GpuEventGuard::new(evt, A::HOST_PTR || DA::HOST_PTR, &ids),
+        ))
+    }
+}
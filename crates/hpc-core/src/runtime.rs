@@ -0,0 +1,204 @@
+//! src/runtime.rs
+//
+// Plattform-/Device-Auswahl statt `get_platforms()?.remove(0)` in jedem
+// Beispiel: Typ, Vendor, Name, Env-Override, CPU-Fallback (z.B. PoCL).
+
+use crate::{BufferPool, ClError, GpuBuffer, PinnedHostBuffer, Queued, StreamPair};
+use bytemuck::Pod;
+use opencl3::{
+    command_queue::CommandQueue,
+    context::Context,
+    device::{Device, CL_DEVICE_TYPE_ACCELERATOR, CL_DEVICE_TYPE_ALL, CL_DEVICE_TYPE_CPU, CL_DEVICE_TYPE_GPU},
+    error_codes::{CL_DEVICE_NOT_FOUND, CL_PLATFORM_NOT_FOUND_KHR},
+    platform::get_platforms,
+    types::{cl_command_queue_properties, cl_device_type},
+};
+use std::env;
+
+/// Overrides the device: `gpu`, `cpu`, `accelerator`, `all` or a name substring;
+/// a set value disables the CPU fallback.
+pub const DEVICE_ENV: &str = "HPC_DEVICE";
+
+/// Overrides the platform: substring of platform name or vendor.
+pub const PLATFORM_ENV: &str = "HPC_PLATFORM";
+
+/// Selected device with its `Context` and default `CommandQueue`.
+pub struct GpuContext {
+    device: Device,
+    context: Context,
+    queue: CommandQueue,
+}
+
+impl GpuContext {
+    /// First GPU, honouring the environment overrides, with CPU fallback.
+    pub fn new() -> Result<Self, ClError> { Self::builder().build() }
+
+    pub fn builder() -> GpuContextBuilder { GpuContextBuilder::default() }
+
+    pub fn device(&self) -> &Device { &self.device }
+
+    pub fn context(&self) -> &Context { &self.context }
+
+    /// Default queue, created with the builder's queue properties.
+    pub fn queue(&self) -> &CommandQueue { &self.queue }
+
+    /// Additional queue on the same device (e.g. a separate transfer queue).
+    pub fn create_queue(&self, props: cl_command_queue_properties) -> Result<CommandQueue, ClError> {
+        CommandQueue::create(&self.context, self.device.id(), props)
+            .map_err(|e| ClError::from(e).context("GpuContext::create_queue", None))
+    }
+
+    /// Fresh transfer and compute queue with `props` for overlapping
+    /// copies with kernels.
+    pub fn stream_pair(&self, props: cl_command_queue_properties) -> Result<StreamPair, ClError> {
+        Ok(StreamPair::new(self.create_queue(props)?, self.create_queue(props)?))
+    }
+
+    /// Allocates a buffer for `len` elements in this context.
+    pub fn buffer<T: Pod>(&self, len: usize) -> Result<GpuBuffer<T, Queued>, ClError> {
+        GpuBuffer::new(&self.context, len)
+    }
+
+    /// Buffer pool for `T` in this context caching up to `max_bytes`.
+    pub fn pool<T: Pod>(&self, max_bytes: usize) -> BufferPool<'_, T> {
+        BufferPool::new(&self.context, max_bytes)
+    }
+
+    /// Pinned host memory for `len` elements, mapped through the default queue.
+    pub fn pinned<T: Pod>(&self, len: usize) -> Result<PinnedHostBuffer<'_, T>, ClError> {
+        PinnedHostBuffer::new(&self.context, &self.queue, len)
+    }
+}
+
+/// Builder for [`GpuContext`]; all filters are case-insensitive substrings.
+#[derive(Clone, Debug)]
+pub struct GpuContextBuilder {
+    device_type: cl_device_type,
+    platform: Option<String>,
+    name: Option<String>,
+    cpu_fallback: bool,
+    use_env: bool,
+    queue_props: cl_command_queue_properties,
+}
+
+impl Default for GpuContextBuilder {
+    fn default() -> Self {
+        Self {
+            device_type: CL_DEVICE_TYPE_GPU,
+            platform: None,
+            name: None,
+            cpu_fallback: true,
+            use_env: true,
+            queue_props: 0,
+        }
+    }
+}
+
+impl GpuContextBuilder {
+    /// `CL_DEVICE_TYPE_*` to look for (default: GPU).
+    pub fn device_type(mut self, device_type: cl_device_type) -> Self {
+        self.device_type = device_type;
+        self
+    }
+
+    /// Platform name, platform vendor or device vendor must contain `vendor`.
+    pub fn vendor(mut self, vendor: &str) -> Self {
+        self.platform = Some(vendor.to_lowercase());
+        self
+    }
+
+    /// Device name must contain `name`.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_lowercase());
+        self
+    }
+
+    /// Fall back to a CPU device matching the vendor / name filters if no
+    /// device of the requested type matches (default: on). An explicit
+    /// [`DEVICE_ENV`] choice never falls back.
+    pub fn cpu_fallback(mut self, on: bool) -> Self {
+        self.cpu_fallback = on;
+        self
+    }
+
+    /// Ignore [`DEVICE_ENV`] / [`PLATFORM_ENV`].
+    pub fn ignore_env(mut self) -> Self {
+        self.use_env = false;
+        self
+    }
+
+    /// Properties of the default queue, e.g. `CL_QUEUE_PROFILING_ENABLE`.
+    pub fn queue_properties(mut self, props: cl_command_queue_properties) -> Self {
+        self.queue_props = props;
+        self
+    }
+
+    pub fn build(mut self) -> Result<GpuContext, ClError> {
+        if self.use_env { self.apply_env(); }
+
+        let id = match self.select(self.device_type, self.platform.as_deref(), self.name.as_deref())? {
+            Some(id) => id,
+            None if self.cpu_fallback && self.device_type != CL_DEVICE_TYPE_CPU => {
+                self.select(CL_DEVICE_TYPE_CPU, self.platform.as_deref(), self.name.as_deref())?
+                    .ok_or_else(|| ClError::from(CL_DEVICE_NOT_FOUND).context("GpuContext::build", None))?
+            }
+            None => return Err(ClError::from(CL_DEVICE_NOT_FOUND).context("GpuContext::build", None)),
+        };
+
+        let device = Device::new(id);
+        let context = Context::from_device(&device)
+            .map_err(|e| ClError::from(e).context("GpuContext::build", None))?;
+        let queue = CommandQueue::create(&context, id, self.queue_props)
+            .map_err(|e| ClError::from(e).context("GpuContext::build", None))?;
+        Ok(GpuContext { device, context, queue })
+    }
+
+    // Env-Variablen überschreiben Typ / Name / Plattform
+    fn apply_env(&mut self) {
+        if let Ok(dev) = env::var(DEVICE_ENV) {
+            let dev = dev.trim().to_lowercase();
+            // ausdrücklich gewähltes Device: kein stiller Wechsel auf die CPU
+            if !dev.is_empty() { self.cpu_fallback = false; }
+            match dev.as_str() {
+                ""            => {}
+                "gpu"         => self.device_type = CL_DEVICE_TYPE_GPU,
+                "cpu"         => self.device_type = CL_DEVICE_TYPE_CPU,
+                "accelerator" => self.device_type = CL_DEVICE_TYPE_ACCELERATOR,
+                "all"         => self.device_type = CL_DEVICE_TYPE_ALL,
+                _             => { self.device_type = CL_DEVICE_TYPE_ALL; self.name = Some(dev); }
+            }
+        }
+        if let Ok(p) = env::var(PLATFORM_ENV) && !p.trim().is_empty() {
+            self.platform = Some(p.trim().to_lowercase());
+        }
+    }
+
+    fn select(
+        &self,
+        device_type: cl_device_type,
+        vendor: Option<&str>,
+        name: Option<&str>,
+    ) -> Result<Option<opencl3::types::cl_device_id>, ClError> {
+        let platforms = get_platforms()
+            .map_err(|e| ClError::from(e).context("GpuContext::build", None))?;
+        if platforms.is_empty() {
+            return Err(ClError::from(CL_PLATFORM_NOT_FOUND_KHR).context("GpuContext::build", None));
+        }
+
+        let contains = |s: Result<String, _>, pat: &str| s.map(|s| s.to_lowercase().contains(pat)).unwrap_or(false);
+
+        for platform in platforms {
+            // Plattform ohne passende Devices liefert CL_DEVICE_NOT_FOUND → überspringen
+            let Ok(ids) = platform.get_devices(device_type) else { continue };
+            for id in ids {
+                let device = Device::new(id);
+                let vendor_ok = vendor.is_none_or(|v| {
+                    contains(platform.name(), v) || contains(platform.vendor(), v) || contains(device.vendor(), v)
+                });
+                let name_ok = name.is_none_or(|n| contains(device.name(), n));
+                if vendor_ok && name_ok { return Ok(Some(id)); }
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,144 @@
+//! src/scope.rs
+//
+// Leak-sichere Host-Transfers: der Scope merkt sich jedes Event und wartet
+// beim Verlassen (auch per Panic) auf alle. Ein vergessener Guard
+// (`mem::forget`) kann den Host-Speicher so nicht vorzeitig freigeben.
+
+use crate::{Backend, EnqueueError, Enqueued, GpuBuffer, GpuEventGuard, HostRead, HostWrite, OpenCl, Queued, Ready, Rect, Writable};
+use bytemuck::Pod;
+use std::{cell::RefCell, marker::PhantomData, ops::Range};
+
+/// Host transfers whose host memory outlives the scope; see [`transfer_scope`].
+pub struct TransferScope<'env, B: Backend = OpenCl> {
+    events: RefCell<Vec<B::Event>>,
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+/// Runs `f` with a [`TransferScope`] and waits for every transfer enqueued
+/// through it before returning, also when `f` panics.
+///
+/// The plain `enqueue_*` transfers are `unsafe` because a leaked guard
+/// (`mem::forget`) would end the host borrow early. Here the host slices are
+/// borrowed for `'env`, which outlives the whole call, not just the guard,
+/// so the scope's transfers are safe.
+///
+/// ```
+/// # use hpc_core::{transfer_scope, GpuBuffer, Mock, MockContext, MockQueue, Queued};
+/// let ctx = MockContext::new();
+/// let queue = MockQueue::new(&ctx);
+/// let host = vec![1u32; 4];
+/// let buf = transfer_scope(|s| {
+///     let (inflight, guard) = s.write(GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap(), &queue, &host).unwrap();
+///     inflight.into_ready(guard)
+/// });
+/// # let _ = buf;
+/// ```
+///
+/// ```compile_fail
+/// # use hpc_core::{transfer_scope, GpuBuffer, Mock, MockContext, MockQueue, Queued};
+/// # let ctx = MockContext::new();
+/// # let queue = MockQueue::new(&ctx);
+/// transfer_scope(|s| {
+///     let host = vec![1u32; 4];
+///     let (_inflight, guard) = s.write(GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap(), &queue, &host).unwrap();
+///     std::mem::forget(guard); // error: `host` does not outlive the scope
+/// });
+/// ```
+pub fn transfer_scope<'env, B: Backend, R>(f: impl FnOnce(&TransferScope<'env, B>) -> R) -> R {
+    let scope = TransferScope { events: RefCell::new(Vec::new()), _env: PhantomData };
+    f(&scope)
+}
+
+impl<B: Backend> Drop for TransferScope<'_, B> {
+    fn drop(&mut self) {
+        for evt in self.events.get_mut().drain(..) {
+            let _ = B::wait(&evt);
+        }
+    }
+}
+
+impl<'env, B: Backend> TransferScope<'env, B> {
+
+    /// [`enqueue_write`](GpuBuffer::enqueue_write) within the scope.
+    pub fn write<T: Pod, A: HostWrite + 'env>(
+        &self,
+        buf: GpuBuffer<T, Queued, B, A>,
+        queue: &B::Queue,
+        host: &'env [T],
+    ) -> Enqueued<'env, T, Queued, B, A> {
+        // SAFETY: `host` lebt über `'env`, der Scope wartet vor dessen Ende
+        self.track(unsafe { buf.enqueue_write(queue, host) })
+    }
+
+    /// [`enqueue_write_at`](GpuBuffer::enqueue_write_at) within the scope.
+    pub fn write_at<T: Pod, S: Writable, A: HostWrite + 'env>(
+        &self,
+        buf: GpuBuffer<T, S, B, A>,
+        queue: &B::Queue,
+        offset: usize,
+        host: &'env [T],
+    ) -> Enqueued<'env, T, S, B, A> {
+        // SAFETY: `host` lebt über `'env`, der Scope wartet vor dessen Ende
+        self.track(unsafe { buf.enqueue_write_at(queue, offset, host) })
+    }
+
+    /// [`enqueue_read`](GpuBuffer::enqueue_read) within the scope.
+    pub fn read<T: Pod, A: HostRead + 'env>(
+        &self,
+        buf: GpuBuffer<T, Ready, B, A>,
+        queue: &B::Queue,
+        host_out: &'env mut [T],
+    ) -> Enqueued<'env, T, Ready, B, A> {
+        // SAFETY: `host_out` lebt über `'env`, der Scope wartet vor dessen Ende
+        self.track(unsafe { buf.enqueue_read(queue, host_out) })
+    }
+
+    /// [`enqueue_read_range`](GpuBuffer::enqueue_read_range) within the scope.
+    pub fn read_range<T: Pod, A: HostRead + 'env>(
+        &self,
+        buf: GpuBuffer<T, Ready, B, A>,
+        queue: &B::Queue,
+        range: Range<usize>,
+        host_out: &'env mut [T],
+    ) -> Enqueued<'env, T, Ready, B, A> {
+        // SAFETY: `host_out` lebt über `'env`, der Scope wartet vor dessen Ende
+        self.track(unsafe { buf.enqueue_read_range(queue, range, host_out) })
+    }
+
+    /// [`enqueue_write_rect`](GpuBuffer::enqueue_write_rect) within the scope.
+    pub fn write_rect<T: Pod, S: Writable, A: HostWrite + 'env>(
+        &self,
+        buf: GpuBuffer<T, S, B, A>,
+        queue: &B::Queue,
+        rect: Rect,
+        host: &'env [T],
+        host_rect: Rect,
+    ) -> Enqueued<'env, T, S, B, A> {
+        // SAFETY: `host` lebt über `'env`, der Scope wartet vor dessen Ende
+        self.track(unsafe { buf.enqueue_write_rect(queue, rect, host, host_rect) })
+    }
+
+    /// [`enqueue_read_rect`](GpuBuffer::enqueue_read_rect) within the scope.
+    pub fn read_rect<T: Pod, A: HostRead + 'env>(
+        &self,
+        buf: GpuBuffer<T, Ready, B, A>,
+        queue: &B::Queue,
+        rect: Rect,
+        host_out: &'env mut [T],
+        host_rect: Rect,
+    ) -> Enqueued<'env, T, Ready, B, A> {
+        // SAFETY: `host_out` lebt über `'env`, der Scope wartet vor dessen Ende
+        self.track(unsafe { buf.enqueue_read_rect(queue, rect, host_out, host_rect) })
+    }
+
+    // Event für das Warten am Scope-Ende merken; ohne Handle sofort warten
+    fn track<R, E>(&self, res: Result<(R, GpuEventGuard<'env, B>), EnqueueError<E>>) -> Result<(R, GpuEventGuard<'env, B>), EnqueueError<E>> {
+        if let Ok((_, guard)) = &res {
+            match B::retain(&guard.evt) {
+                Ok(evt) => self.events.borrow_mut().push(evt),
+                Err(_)  => { let _ = B::wait(&guard.evt); }
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,141 @@
+//! src/split.rs
+//
+// Sub-Buffer: disjunkte Bereiche eines GpuBuffer mit eigenem Typ-State
+// (z.B. Chunks parallel hochladen), Rückführung in den Parent, sobald
+// alle Teile Ready sind.
+
+use crate::{next_id, Access, Backend, ClError, EnqueueError, GpuBuffer, OpenCl, PairWritable, Queued, Ready, ReadWrite};
+use bytemuck::Pod;
+use std::{marker::PhantomData, mem, ops::Range};
+
+#[cfg(feature = "metrics")]
+use crate::record;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Sub-buffers of a split, in the order of [`SplitBuffer::ranges`].
+pub type Parts<T, S, B = OpenCl, A = ReadWrite> = Vec<GpuBuffer<T, S, B, A>>;
+
+/// Result of a split: parent plus parts, or the untouched buffer with the error.
+pub type SplitResult<T, S, B = OpenCl, A = ReadWrite> =
+    Result<(SplitBuffer<T, B, A>, Parts<T, S, B, A>), EnqueueError<GpuBuffer<T, S, B, A>>>;
+
+/// Result of a rejoin: the Ready parent, or split and parts with the error.
+pub type JoinResult<T, B = OpenCl, A = ReadWrite> =
+    Result<GpuBuffer<T, Ready, B, A>, EnqueueError<(SplitBuffer<T, B, A>, Parts<T, Ready, B, A>)>>;
+
+/// Parent of a split: keeps the storage while its regions are lent out as
+/// sub-buffers (which inherit the parent's flags `A`). [`join`](Self::join)
+/// turns it back into a Ready buffer.
+pub struct SplitBuffer<T: Pod, B: Backend = OpenCl, A: Access = ReadWrite> {
+    parent: GpuBuffer<T, Queued, B, A>,
+    // ID jedes Sub-Buffers (nie wiederverwendet, anders als mem_id) und sein Bereich
+    parts: Box<[(u64, Range<usize>)]>,
+}
+
+impl<T: Pod, S: PairWritable, B: Backend, A: Access> GpuBuffer<T, S, B, A> {
+
+    /// Granularity of sub-buffer origins in elements, derived from
+    /// `CL_DEVICE_MEM_BASE_ADDR_ALIGN`.
+    pub fn split_align(&self) -> Result<usize, ClError> {
+        let align = B::base_align(&self.buf).map_err(|e| e.context("split_align", None))?;
+        let size = mem::size_of::<T>().max(1);
+        Ok(align / gcd(align, size))
+    }
+
+    /// Splits into sub-buffers of `lens` elements each. The lengths must
+    /// add up to [`len`](Self::len) and every origin must be aligned to
+    /// [`split_align`](Self::split_align). Each part keeps the state `S`.
+    pub fn split(self, lens: &[usize]) -> SplitResult<T, S, B, A> {
+
+        #[cfg(feature="metrics")]
+        let t = Instant::now();
+
+        let total: usize = lens.iter().sum();
+        if total != self.len {
+            let err = ClError::SizeMismatch { expected: self.len, actual: total };
+            return Err(EnqueueError::new(self, err));
+        }
+        let align = match B::base_align(&self.buf) {
+            Ok(align) => align,
+            Err(e)    => return Err(EnqueueError::new(self, e.context("split", None))),
+        };
+
+        let mut children = Vec::with_capacity(lens.len());
+        let mut parts = Vec::with_capacity(lens.len());
+        let mut offset = 0;
+        for &len in lens {
+            let origin = offset * mem::size_of::<T>();
+            if !origin.is_multiple_of(align) {
+                // bereits erzeugte Sub-Buffer werden hier freigegeben
+                drop(children);
+                return Err(EnqueueError::new(self, ClError::Misaligned { offset: origin, align }));
+            }
+            let buf = match B::sub_buffer(&self.buf, offset, len) {
+                Ok(buf) => buf,
+                Err(e)  => {
+                    drop(children);
+                    let err = e.context("split", Some(len * mem::size_of::<T>()));
+                    return Err(EnqueueError::new(self, err));
+                }
+            };
+            let id = next_id();
+            parts.push((id, offset..offset + len));
+            children.push(GpuBuffer { buf, len, id, parent: self.id, _state: PhantomData });
+            offset += len;
+        }
+
+        #[cfg(feature="metrics")]
+        record("split", t);
+
+        Ok((SplitBuffer { parent: self.into_state(), parts: parts.into() }, children))
+    }
+
+    /// Splits into at most `n` parts of equal, alignment-rounded length
+    /// (the last part takes the remainder).
+    pub fn split_even(self, n: usize) -> SplitResult<T, S, B, A> {
+        let step = match self.split_align() {
+            Ok(step) => step,
+            Err(e)   => return Err(EnqueueError::new(self, e)),
+        };
+        let chunk = self.len.div_ceil(n.max(1)).next_multiple_of(step).max(1);
+        let lens: Vec<usize> = (0..self.len).step_by(chunk).map(|o| chunk.min(self.len - o)).collect();
+        self.split(&lens)
+    }
+}
+
+impl<T: Pod, B: Backend, A: Access> SplitBuffer<T, B, A> {
+
+    /// Element ranges of the parts within the parent, in split order.
+    pub fn ranges(&self) -> impl ExactSizeIterator<Item = Range<usize>> + '_ {
+        self.parts.iter().map(|(_, r)| r.clone())
+    }
+
+    /// Length of the parent in elements.
+    pub fn len(&self) -> usize { self.parent.len }
+
+    pub fn is_empty(&self) -> bool { self.parent.len == 0 }
+
+    /// Rejoins all parts, in split order, into the Ready parent. On error
+    /// split and parts come back unchanged.
+    pub fn join(self, children: Parts<T, Ready, B, A>) -> JoinResult<T, B, A> {
+        if children.len() != self.parts.len() {
+            let err = ClError::SubBufferCount { expected: self.parts.len(), actual: children.len() };
+            return Err(EnqueueError::new((self, children), err));
+        }
+        let foreign = children.iter().zip(&self.parts)
+            .position(|(c, (id, r))| c.id != *id || c.parent != self.parent.id || c.len != r.len());
+        if let Some(index) = foreign {
+            return Err(EnqueueError::new((self, children), ClError::SubBufferMismatch { index }));
+        }
+
+        #[cfg(feature="metrics")]
+        record("join", Instant::now());
+
+        drop(children);
+        // neue ID: ein StreamPair, dem der Parent vor dem Split gehörte, kennt ihn so nicht mehr
+        Ok(GpuBuffer { id: next_id(), ..self.parent.into_state() })
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize { if b == 0 { a } else { gcd(b, a % b) } }
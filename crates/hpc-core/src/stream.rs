@@ -0,0 +1,317 @@
+//! src/stream.rs
+//
+// Transfer-Queue + Compute-Queue auf demselben Device: H2D/D2H laufen auf
+// der einen, Kernel/Copy/Fill auf der anderen. Die Abhängigkeiten zwischen
+// den Queues setzt das StreamPair pro Buffer selbst, Kopien überlappen so
+// ohne Handarbeit mit Kernels auf anderen Buffern.
+
+use crate::{
+    launch::launch_unchecked, Access, Backend, ClError, Copied, EnqueueError, GpuBuffer, GpuEventGuard, HostRead,
+    HostWrite, InFlightSet, KernelArgs, MemKey, NdRange, OpenCl, PairReadable, PairWritable, Paired, Queued, Ready, ReadWrite,
+};
+use bytemuck::Pod;
+use opencl3::{event::CL_COMPLETE, kernel::Kernel};
+use std::{cell::RefCell, collections::{HashMap, HashSet}, ops::Range};
+
+/// Result of a [`StreamPair`] command: the buffer as [`Paired`] for later
+/// commands of the same pair, plus the guard of the command (keeps the host
+/// memory borrowed); or the untouched buffer together with the error.
+pub type Streamed<'a, T, S, B = OpenCl, A = ReadWrite> =
+    Result<(GpuBuffer<T, Paired, B, A>, GpuEventGuard<'a, B>), EnqueueError<GpuBuffer<T, S, B, A>>>;
+
+/// Result of [`StreamPair::into_ready`]: the Ready buffer, or the buffer
+/// still [`Paired`] together with [`ClError::PairMismatch`].
+pub type Released<T, B = OpenCl, A = ReadWrite> =
+    Result<GpuBuffer<T, Ready, B, A>, EnqueueError<GpuBuffer<T, Paired, B, A>>>;
+
+/// [`Streamed`] for [`StreamPair::copy`]: source and destination.
+pub type StreamCopied<'a, T, S, D, B = OpenCl, A = ReadWrite, DA = ReadWrite> = Result<
+    ((GpuBuffer<T, Paired, B, A>, GpuBuffer<T, Paired, B, DA>), GpuEventGuard<'a, B>),
+    EnqueueError<(GpuBuffer<T, S, B, A>, GpuBuffer<T, D, B, DA>)>,
+>;
+
+/// [`Streamed`] for [`StreamPair::launch`]: all buffer arguments.
+pub type StreamLaunched<'a, A> =
+    Result<(<<A as KernelArgs>::InFlight<'a> as InFlightSet<'a>>::Paired, GpuEventGuard<'a>), EnqueueError<A>>;
+
+// letztes Kommando je Buffer (ID) mit Eltern-Buffer bei Sub-Buffern
+type LastCommands<B> = HashMap<u64, (Option<u64>, <B as Backend>::Event)>;
+
+#[derive(Clone, Copy)]
+enum Side { Transfer, Compute }
+
+/// A transfer queue and a compute queue on one device.
+///
+/// Uploads and downloads go to the transfer queue, kernels, copies and fills
+/// to the compute queue. Every command waits for the last command that used
+/// one of its buffers (on either queue, so out-of-order queues stay ordered
+/// too), hence transfers of one buffer overlap with kernels on another
+/// without manual events. Commands on a sub-buffer are also ordered against
+/// its parent (not against other sub-buffers, which must not overlap).
+///
+/// Returned buffers are [`Paired`]: only commands of the same pair accept
+/// them (others fail with [`ClError::PairMismatch`]),
+/// [`into_ready`](Self::into_ready) waits and makes them Ready for
+/// everything else. A failed command stays recorded, so later commands on
+/// its buffers fail as well instead of running on undefined contents.
+///
+/// ```compile_fail
+/// # use hpc_core::{GpuBuffer, Mock, MockContext, MockQueue, Queued, StreamPair};
+/// let ctx = MockContext::new();
+/// let streams = StreamPair::<Mock>::new(MockQueue::new(&ctx), MockQueue::new(&ctx));
+/// let (buf, _guard) = unsafe { streams.write(GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap(), &[1; 4]) }.unwrap();
+/// let view = buf.map_read(streams.transfer()); // error: the upload may still be running
+/// ```
+pub struct StreamPair<B: Backend = OpenCl> {
+    transfer: B::Queue,
+    compute: B::Queue,
+    last: RefCell<LastCommands<B>>,
+    // IDs der Buffer, die das Paar als Paired ausgegeben hat (bis into_ready)
+    owned: RefCell<HashSet<u64>>,
+}
+
+impl<B: Backend> StreamPair<B> {
+    pub fn new(transfer: B::Queue, compute: B::Queue) -> Self {
+        Self { transfer, compute, last: RefCell::new(HashMap::new()), owned: RefCell::new(HashSet::new()) }
+    }
+
+    pub fn transfer(&self) -> &B::Queue { &self.transfer }
+
+    pub fn compute(&self) -> &B::Queue { &self.compute }
+
+    /// Waits for the pair's last command on `buf` (and on its parent and
+    /// sub-buffers) and hands the buffer back Ready. A buffer of another
+    /// pair comes back unchanged with [`ClError::PairMismatch`]; failed
+    /// commands are reported by their guards.
+    pub fn into_ready<T: Pod, A: Access>(&self, buf: GpuBuffer<T, Paired, B, A>) -> Released<T, B, A> {
+        let key = buf.mem_key();
+        if let Err(err) = self.owns(&[key]) {
+            return Err(EnqueueError::new(buf, err));
+        }
+        let mut last = self.last.borrow_mut();
+        for evt in pending::<B>(&last, &[key]) {
+            let _ = B::wait(evt);
+        }
+        // auch fehlgeschlagene Einträge enden hier, der Buffer verlässt das Paar
+        last.retain(|&id, (parent, _)| id != key.id && *parent != Some(key.id));
+        self.owned.borrow_mut().remove(&key.id);
+        Ok(buf.into_state())
+    }
+
+    // frischer Buffer ohne Kommandos direkt als Paired dieses Paars (Pipeline)
+    pub(crate) fn adopt<T: Pod, A: Access>(&self, buf: GpuBuffer<T, Queued, B, A>) -> GpuBuffer<T, Paired, B, A> {
+        self.owned.borrow_mut().insert(buf.id);
+        buf.into_state()
+    }
+
+    /// Uploads `host` into the whole buffer on the transfer queue.
+    ///
+    /// # Safety
+    ///
+    /// As for [`GpuBuffer::enqueue_write`]: the returned guard must not be leaked.
+    pub unsafe fn write<'a, T: Pod, S: PairWritable, A: HostWrite + 'a>(
+        &self,
+        buf: GpuBuffer<T, S, B, A>,
+        host: &'a [T],
+    ) -> Streamed<'a, T, S, B, A> {
+        if host.len() != buf.len {
+            let err = ClError::SizeMismatch { expected: buf.len, actual: host.len() };
+            return Err(EnqueueError::new(buf, err));
+        }
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.write_at(buf, 0, host) }
+    }
+
+    /// Uploads `host` to elements `offset..offset + host.len()` on the transfer queue.
+    ///
+    /// # Safety
+    ///
+    /// As for [`GpuBuffer::enqueue_write`]: the returned guard must not be leaked.
+    pub unsafe fn write_at<'a, T: Pod, S: PairWritable, A: HostWrite + 'a>(
+        &self,
+        buf: GpuBuffer<T, S, B, A>,
+        offset: usize,
+        host: &'a [T],
+    ) -> Streamed<'a, T, S, B, A> {
+        let ids = [buf.mem_key()];
+        if let Err(err) = buf.check_range(offset, host.len()).and(self.owns(&ids)) {
+            return Err(EnqueueError::new(buf, err));
+        }
+        self.enqueue(Side::Transfer, &ids, |queue, wait| {
+            // SAFETY: Vertrag des Aufrufers
+            unsafe { buf.into_state::<Ready>().write_unchecked(queue, offset, host, wait) }.map_err(relabel)
+        })
+        .map(|(buf, guard)| (buf.into_state(), guard))
+    }
+
+    /// Downloads the whole buffer into `host_out` on the transfer queue.
+    ///
+    /// # Safety
+    ///
+    /// As for [`GpuBuffer::enqueue_read`]: the returned guard must not be leaked.
+    pub unsafe fn read<'a, T: Pod, S: PairReadable, A: HostRead + 'a>(
+        &self,
+        buf: GpuBuffer<T, S, B, A>,
+        host_out: &'a mut [T],
+    ) -> Streamed<'a, T, S, B, A> {
+        if host_out.len() != buf.len {
+            let err = ClError::SizeMismatch { expected: buf.len, actual: host_out.len() };
+            return Err(EnqueueError::new(buf, err));
+        }
+        let len = buf.len;
+        // SAFETY: Vertrag des Aufrufers
+        unsafe { self.read_range(buf, 0..len, host_out) }
+    }
+
+    /// Downloads the elements in `range` into `host_out` on the transfer queue.
+    ///
+    /// # Safety
+    ///
+    /// As for [`GpuBuffer::enqueue_read`]: the returned guard must not be leaked.
+    pub unsafe fn read_range<'a, T: Pod, S: PairReadable, A: HostRead + 'a>(
+        &self,
+        buf: GpuBuffer<T, S, B, A>,
+        range: Range<usize>,
+        host_out: &'a mut [T],
+    ) -> Streamed<'a, T, S, B, A> {
+        let ids = [buf.mem_key()];
+        let count = match buf.check_span(&range).and_then(|count| self.owns(&ids).map(|_| count)) {
+            Ok(count) => count,
+            Err(err)  => return Err(EnqueueError::new(buf, err)),
+        };
+        if host_out.len() != count {
+            let err = ClError::SizeMismatch { expected: count, actual: host_out.len() };
+            return Err(EnqueueError::new(buf, err));
+        }
+        self.enqueue(Side::Transfer, &ids, |queue, wait| {
+            // SAFETY: Vertrag des Aufrufers
+            unsafe { buf.into_state::<Ready>().read_unchecked(queue, range.start, host_out, wait) }.map_err(relabel)
+        })
+        .map(|(buf, guard)| (buf.into_state(), guard))
+    }
+
+    /// Copies `src` into `dst` (same length) on the compute queue.
+    pub fn copy<'a, T: Pod, S: PairReadable, A: Access + 'a, D: PairWritable, DA: Access + 'a>(
+        &self,
+        src: GpuBuffer<T, S, B, A>,
+        dst: GpuBuffer<T, D, B, DA>,
+    ) -> StreamCopied<'a, T, S, D, B, A, DA> {
+        if dst.len != src.len {
+            let err = ClError::SizeMismatch { expected: src.len, actual: dst.len };
+            return Err(EnqueueError::new((src, dst), err));
+        }
+        let (ids, len) = ([src.mem_key(), dst.mem_key()], src.len);
+        if let Err(err) = self.owns(&ids) {
+            return Err(EnqueueError::new((src, dst), err));
+        }
+        self.enqueue(Side::Compute, &ids, |queue, wait| {
+            let copied: Copied<'a, T, Ready, B, A, DA> =
+                src.into_state::<Ready>().copy_unchecked(queue, 0, dst.into_state::<Ready>(), 0, len, wait);
+            copied.map_err(|e| {
+                let ((src, dst), err) = e.into_parts();
+                EnqueueError::new((src.into_state(), dst.into_state()), err)
+            })
+        })
+        .map(|(set, guard)| (set.pair(), guard))
+    }
+
+    /// Sets every element to `value` on the compute queue.
+    pub fn fill<'a, T: Pod, S: PairWritable, A: Access + 'a>(
+        &self,
+        buf: GpuBuffer<T, S, B, A>,
+        value: T,
+    ) -> Streamed<'a, T, S, B, A> {
+        let (ids, len) = ([buf.mem_key()], buf.len);
+        if let Err(err) = self.owns(&ids) {
+            return Err(EnqueueError::new(buf, err));
+        }
+        self.enqueue(Side::Compute, &ids, |queue, wait| {
+            buf.into_state::<Ready>().fill_unchecked(queue, 0, len, value, wait).map_err(relabel)
+        })
+        .map(|(buf, guard)| (buf.into_state(), guard))
+    }
+
+    // Kommando auf `side` hinter dem letzten Kommando auf `ids`, auf ihren
+    // Eltern- und Sub-Buffern; Events der eigenen Queue bleiben in der Liste
+    // (Out-of-Order-Queues)
+    fn enqueue<'a, R, E>(
+        &self,
+        side: Side,
+        ids: &[MemKey],
+        f: impl FnOnce(&B::Queue, &[&B::Event]) -> Result<(R, GpuEventGuard<'a, B>), E>,
+    ) -> Result<(R, GpuEventGuard<'a, B>), E> {
+        let queue = match side {
+            Side::Transfer => &self.transfer,
+            Side::Compute  => &self.compute,
+        };
+        let (out, guard) = {
+            let last = self.last.borrow();
+            f(queue, &pending::<B>(&last, ids))?
+        };
+        self.record(ids, &guard.evt);
+        Ok((out, guard))
+    }
+
+    // `evt` als letztes Kommando auf `ids` merken, fertige Einträge verwerfen.
+    // Fehlgeschlagene bleiben: Folgekommandos warten darauf und scheitern ebenfalls
+    fn record(&self, ids: &[MemKey], evt: &B::Event) {
+        let mut last = self.last.borrow_mut();
+        last.retain(|_, (_, e)| !matches!(B::status(e), Ok(CL_COMPLETE)));
+        self.owned.borrow_mut().extend(ids.iter().map(|key| key.id));
+        for key in ids {
+            match B::retain(evt) {
+                Ok(e)  => { last.insert(key.id, (key.parent, e)); }
+                // ohne Handle lässt sich die Reihenfolge nur durch Warten sichern
+                Err(_) => { last.remove(&key.id); let _ = B::wait(evt); }
+            }
+        }
+    }
+
+    // Paired-Buffer eines anderen Paars: dessen Kommandos sind hier unbekannt.
+    // Sub-Buffer eines eigenen Paired-Buffers gehören mit dazu
+    fn owns(&self, ids: &[MemKey]) -> Result<(), ClError> {
+        let owned = self.owned.borrow();
+        let own = |key: &MemKey| !key.paired || owned.contains(&key.id) || key.parent.is_some_and(|p| owned.contains(&p));
+        match ids.iter().all(own) {
+            true  => Ok(()),
+            false => Err(ClError::PairMismatch),
+        }
+    }
+}
+
+impl StreamPair {
+
+    /// Launches `kernel` on the compute queue after all pending transfers of
+    /// its buffer arguments; see [`launch`](crate::launch).
+    pub fn launch<'a, A: KernelArgs + 'a>(
+        &self,
+        kernel: &Kernel,
+        range: &NdRange,
+        args: A,
+    ) -> StreamLaunched<'a, A> {
+        let mut ids = Vec::new();
+        args.mem_ids(&mut ids);
+        if let Err(err) = self.owns(&ids) {
+            return Err(EnqueueError::new(args, err));
+        }
+        self.enqueue(Side::Compute, &ids, |queue, wait| launch_unchecked(queue, kernel, range, args, wait))
+            .map(|(set, guard)| (set.pair(), guard))
+    }
+}
+
+// letzte Kommandos auf `ids`, ihren Eltern- und Sub-Buffern
+fn pending<'l, B: Backend>(last: &'l LastCommands<B>, ids: &[MemKey]) -> Vec<&'l B::Event> {
+    let mut wait = Vec::new();
+    for key in ids {
+        wait.extend(last.get(&key.id).map(|(_, e)| e));
+        wait.extend(key.parent.and_then(|p| last.get(&p)).map(|(_, e)| e));
+        wait.extend(last.values().filter(|(p, _)| *p == Some(key.id)).map(|(_, e)| e));
+    }
+    wait
+}
+
+// Fehler eines umetikettierten Aufrufs: Buffer im Ausgangszustand zurück
+fn relabel<T: Pod, S, S2, B: Backend, A: Access>(e: EnqueueError<GpuBuffer<T, S, B, A>>) -> EnqueueError<GpuBuffer<T, S2, B, A>> {
+    let (buf, err) = e.into_parts();
+    EnqueueError::new(buf.into_state(), err)
+}
@@ -0,0 +1,21 @@
+// Gemeinsame Helfer der Integrationstests (`mod common;` je Testdatei)
+
+use std::{sync::Arc, task::{Context, Poll, Wake}, thread::{self, Thread}};
+
+// Minimaler Executor: parkt den Thread, bis der Event-Callback weckt
+pub fn block_on<F: std::future::IntoFuture>(fut: F) -> F::Output {
+    struct Unpark(Thread);
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) { self.0.unpark(); }
+    }
+
+    let waker = Arc::new(Unpark(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = std::pin::pin!(fut.into_future());
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(out) => return out,
+            Poll::Pending    => thread::park(),
+        }
+    }
+}
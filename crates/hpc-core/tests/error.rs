@@ -0,0 +1,32 @@
+use hpc_core::{BuildLog, ClCode, ClError};
+
+#[test]
+fn api_error_renders_symbolic_name() {
+    let err = ClError::from(-5);
+    assert_eq!(err.code(), Some(-5));
+    assert_eq!(
+        err.to_string(),
+        "CL_OUT_OF_RESOURCES (-5): failed to allocate resources on the device"
+    );
+}
+
+#[test]
+fn api_error_renders_operation_and_bytes() {
+    let err = ClError::Api { code: ClCode(-61), op: Some("GpuBuffer::new"), bytes: Some(4096) };
+    let msg = err.to_string();
+    assert!(msg.starts_with("GpuBuffer::new (4096 bytes): CL_INVALID_BUFFER_SIZE (-61)"), "{msg}");
+}
+
+#[test]
+fn build_error_lists_every_device_log() {
+    let err = ClError::Build {
+        code: ClCode(-11),
+        logs: vec![
+            BuildLog { device: "gpu0".into(), log: "error: expected ';'\n".into() },
+            BuildLog { device: "gpu1".into(), log: "error: expected ';'".into() },
+        ],
+    };
+    let msg = err.to_string();
+    assert!(msg.starts_with("program build failed: CL_BUILD_PROGRAM_FAILURE (-11)"), "{msg}");
+    assert!(msg.contains("--- gpu0 ---\nerror: expected ';'\n--- gpu1 ---"), "{msg}");
+}
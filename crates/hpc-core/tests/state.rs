@@ -1,11 +1,21 @@
-use hpc_core::{GpuBuffer, Queued, InFlight, Ready};
+use hpc_core::{launch, ClError, GpuBuffer, GpuContext, InFlightSet, Input, NdRange, NotReady, Output, Queued, Ready};
 use opencl3::{
     platform::get_platforms,
     device::{Device, CL_DEVICE_TYPE_GPU},
     context::Context,
     command_queue::CommandQueue,
+    kernel::Kernel,
+    program::Program,
 };
 
+mod common;
+use common::block_on;
+
+// Brauchen ein OpenCL-Device, die weiteren nur mit `cargo test -- --ignored`
+// (Mock-Tests: state_miri.rs).
+// Host-Transfers sind `unsafe` (Guards nicht leak-sicher): kein Test leakt einen Guard.
+fn gpu() -> GpuContext { GpuContext::new().expect("no OpenCL device (HPC_DEVICE / HPC_PLATFORM)") }
+
 #[test]
 fn opencl_typestate_transitions_work() {
     let platform = get_platforms().unwrap().remove(0);
@@ -16,10 +26,112 @@ fn opencl_typestate_transitions_work() {
 
     let host_data = vec![0u8; 4];
 
-    let (inflight, guard) = GpuBuffer::<Queued>::new(&context, 4)
-        .unwrap()
-        .enqueue_write(&queue, &host_data)
-        .unwrap();
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u8, Queued>::new(&context, 4)
+            .unwrap()
+            .enqueue_write(&queue, &host_data)
+    }
+    .unwrap();
+
+    let _ready: GpuBuffer<u8, Ready> = inflight.into_ready(guard);
+}
+
+#[test]
+#[ignore = "needs an OpenCL device"]
+fn opencl_kernel_launch_transitions_work() {
+    let gpu = gpu();
+    let (context, queue) = (gpu.context(), gpu.queue());
+
+    let program = Program::create_and_build_from_source(
+        context,
+        "__kernel void inc(__global const int* a, __global int* b) { size_t i = get_global_id(0); b[i] = a[i] + 1; }",
+        "",
+    ).unwrap();
+    let kernel = Kernel::create(&program, "inc").unwrap();
+
+    let host_in = vec![1i32; 16];
+    let mut host_out = vec![0i32; 16];
+
+    let (a_if, g) = unsafe {
+        GpuBuffer::<i32, Queued>::new(context, 16).unwrap()
+            .enqueue_write(queue, &host_in)
+    }
+    .unwrap();
+    let a = a_if.into_ready(g);
+    let b = GpuBuffer::<i32, Queued>::new(context, 16).unwrap();
+
+    let (k_if, g) = launch(queue, &kernel, &NdRange::d1(16), (Input(0, a), Output(1, b))).unwrap();
+    let (_a, b): (GpuBuffer<i32, Ready>, GpuBuffer<i32, Ready>) = k_if.into_ready(g);
+
+    let (b_if, g) = unsafe { b.enqueue_read(queue, &mut host_out) }.unwrap();
+    let _b = b_if.into_ready(g);
+    assert!(host_out.iter().all(|&x| x == 2));
+}
+
+#[test]
+#[ignore = "needs an OpenCL device"]
+fn opencl_transfer_length_is_validated() {
+    let gpu = gpu();
+    let (context, queue) = (gpu.context(), gpu.queue());
+
+    let too_long = vec![0u8; 8];
+    let err = unsafe {
+        GpuBuffer::<u8, Queued>::new(context, 4).unwrap()
+            .enqueue_write(queue, &too_long)
+    }
+    .err()
+    .unwrap();
+    assert!(matches!(err.error, ClError::SizeMismatch { expected: 4, actual: 8 }));
+
+    // Buffer kommt zurück und kann weiterverwendet werden
+    let (inflight, guard) = unsafe { err.buffer.enqueue_write_at(queue, 2, &too_long[..2]) }.unwrap();
+    let ready: GpuBuffer<u8, Ready> = inflight.into_ready(guard);
+
+    let mut out = vec![0u8; 3];
+    let err = unsafe { ready.enqueue_read_range(queue, 2..6, &mut out) }.err().unwrap();
+    assert!(matches!(err.error, ClError::OutOfRange { offset: 2, count: 4, len: 4 }));
+}
+
+#[test]
+#[ignore = "needs an OpenCL device"]
+fn opencl_try_complete_polls_without_blocking() {
+    let gpu = gpu();
+    let (context, queue) = (gpu.context(), gpu.queue());
+
+    let host_data = vec![7u32; 1 << 16];
+    let (mut inflight, mut guard) = unsafe {
+        GpuBuffer::<u32, Queued>::new(context, host_data.len())
+            .unwrap()
+            .enqueue_write(queue, &host_data)
+    }
+    .unwrap();
+    queue.flush().unwrap();
+
+    let _ready: GpuBuffer<u32, Ready> = loop {
+        match inflight.try_complete(guard) {
+            Ok(ready) => break ready,
+            Err(NotReady::Pending { buffer, guard: g }) => { inflight = buffer; guard = g; }
+            Err(NotReady::Failed { error, .. }) => panic!("write failed: {error}"),
+        }
+        std::thread::yield_now();
+    };
+}
+
+#[test]
+#[ignore = "needs an OpenCL device"]
+fn opencl_event_guard_can_be_awaited() {
+    let gpu = gpu();
+    let (context, queue) = (gpu.context(), gpu.queue());
+
+    let host_data = vec![3u16; 1024];
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u16, Queued>::new(context, host_data.len())
+            .unwrap()
+            .enqueue_write(queue, &host_data)
+    }
+    .unwrap();
+    queue.flush().unwrap();
 
-    let _ready: GpuBuffer<Ready> = inflight.into_ready(guard);
+    let guard = block_on(guard).unwrap();
+    let _ready: GpuBuffer<u16, Ready> = inflight.into_ready(guard);
 }
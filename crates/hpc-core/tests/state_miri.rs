@@ -1,71 +1,755 @@
-use std::marker::PhantomData;
+// Typ-State-Tests gegen das Mock-Backend: keine OpenCL-Aufrufe,
+// läuft ohne GPU und unter Miri (`cargo +nightly miri test --test state_miri`).
+// Host-Transfers sind `unsafe` (Guards nicht leak-sicher): kein Test leakt einen Guard.
 
-// Dummy buffer type without OpenCL dependency 
-struct DummyBuffer(u64);
+use hpc_core::{
+    drop_error_policy, join, join_checked, set_drop_error_policy, transfer_scope, Backend, BufferPool, ClError,
+    DropErrorPolicy, GpuBuffer, GuardSet, HostNoAccess, HostReadOnly, HostWriteOnly, InFlightSet, Mock, MockContext,
+    MockQueue, NotReady, PingPong, PinnedHostBuffer, Pipeline, Queued, ReadOnly, Ready, Rect, SizeClass, StreamPair,
+    UseHostPtr, WriteOnly,
+};
+use std::{sync::{Mutex, MutexGuard}, time::{Duration, Instant}};
 
-// Generic typestate buffer struct
-struct DummyGpuBuffer<S> {
-    buf:    DummyBuffer,
-    len:    usize,
-    _state: PhantomData<S>,
+mod common;
+use common::block_on;
+
+// globale Drop-Policy: Tests, die sie ändern, laufen nacheinander
+static POLICY_LOCK: Mutex<()> = Mutex::new(());
+
+// setzt die Policy, stellt beim Drop (auch per Panic) die vorige wieder her
+struct PolicyReset {
+    prev: DropErrorPolicy,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl PolicyReset {
+    fn set(policy: DropErrorPolicy) -> Self {
+        let lock = POLICY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let prev = drop_error_policy();
+        set_drop_error_policy(policy);
+        Self { prev, _lock: lock }
+    }
 }
 
-// 3. Typestate markers and sealed trait
-mod sealed {
-    pub trait Sealed {}
+impl Drop for PolicyReset {
+    fn drop(&mut self) { set_drop_error_policy(self.prev); }
+}
+
+#[test]
+fn mock_typestate_round_trip() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+
+    let host_in: Vec<u32> = (0..16).collect();
+    let mut host_out = vec![0u32; 16];
+
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 16).unwrap()
+            .enqueue_write(&queue, &host_in)
+    }
+    .unwrap();
+    let ready: GpuBuffer<u32, Ready, Mock> = inflight.into_ready(guard);
+
+    let (inflight, guard) = unsafe { ready.enqueue_read(&queue, &mut host_out) }.unwrap();
+    let _ready = inflight.into_ready(guard);
+    assert_eq!(host_in, host_out);
 }
 
-pub trait State: sealed::Sealed {}
+#[test]
+fn mock_partial_transfers_respect_offsets() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u16, Queued, Mock>::new_in(&ctx, 8).unwrap()
+            .enqueue_write(&queue, &[1; 8])
+    }
+    .unwrap();
+    let halo = [9u16; 2];
+    let (inflight, guard) = unsafe { inflight.into_ready(guard).enqueue_write_at(&queue, 6, &halo) }.unwrap();
+    let ready = inflight.into_ready(guard);
 
-pub struct Queued;
-pub struct InFlight;
-pub struct Ready;
+    let mut out = [0u16; 4];
+    let (inflight, guard) = unsafe { ready.enqueue_read_range(&queue, 4..8, &mut out) }.unwrap();
+    let ready = inflight.into_ready(guard);
+    assert_eq!(out, [1, 1, 9, 9]);
 
-// Implement Sealed for each state
-impl sealed::Sealed for Queued {}
-impl sealed::Sealed for InFlight {}
-impl sealed::Sealed for Ready {}
+    let err = unsafe { ready.enqueue_read_range(&queue, 6..10, &mut out) }.err().unwrap();
+    assert!(matches!(err.error, ClError::OutOfRange { offset: 6, count: 4, len: 8 }));
 
-// Implement State for each marker
-impl State for Queued {}
-impl State for InFlight {}
-impl State for Ready {}
+    // verkehrter Bereich ist kein leerer, leere Transfers lehnt OpenCL ab
+    let (start, end) = (6, 2);
+    let err = unsafe { err.buffer.enqueue_read_range(&queue, start..end, &mut out[..0]) }.err().unwrap();
+    assert!(matches!(err.error, ClError::OutOfRange { offset: 6, count: 0, len: 8 }));
+    let err = unsafe { err.buffer.enqueue_write_at(&queue, 3, &[]) }.err().unwrap();
+    assert!(matches!(err.error, ClError::InvalidArgs { .. }));
+    let err = err.buffer.enqueue_fill_range(&queue, 5..5, 0).err().unwrap();
+    assert!(matches!(err.error, ClError::InvalidArgs { .. }));
+}
 
-//  4. Test: typestate transitions using only dummy types 
 #[test]
-fn typestate_transitions_dummy_only() {
+fn mock_length_mismatch_hands_buffer_back() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
 
-    let dummy = DummyBuffer(12345);
+    let err = unsafe {
+        GpuBuffer::<u8, Queued, Mock>::new_in(&ctx, 4).unwrap()
+            .enqueue_write(&queue, &[0; 8])
+    }
+    .err()
+    .unwrap();
+    assert!(matches!(err.error, ClError::SizeMismatch { expected: 4, actual: 8 }));
+    assert_eq!(err.buffer.len(), 4);
+}
+
+#[test]
+fn mock_try_complete_sees_pending_then_ready() {
+    let ctx = MockContext::with_delay(Duration::from_millis(20));
+    let queue = MockQueue::new(&ctx);
+    let host = vec![5u8; 32];
+
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u8, Queued, Mock>::new_in(&ctx, 32).unwrap()
+            .enqueue_write(&queue, &host)
+    }
+    .unwrap();
 
-    let queued = DummyGpuBuffer::<Queued> {
-        buf:    dummy,
-        len:    42,
-        _state: PhantomData,
+    let Err(NotReady::Pending { buffer, guard }) = inflight.try_complete(guard) else {
+        panic!("delayed command must still be pending");
     };
+    queue.finish();
+    assert!(buffer.try_complete(guard).is_ok());
+}
+
+#[test]
+fn mock_failed_command_is_reported() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+    let host = vec![0u8; 4];
+
+    queue.fail_next(-5);
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u8, Queued, Mock>::new_in(&ctx, 4).unwrap()
+            .enqueue_write(&queue, &host)
+    }
+    .unwrap();
+    queue.finish();
+
+    match inflight.try_complete(guard) {
+        Err(NotReady::Failed { error, .. }) => assert_eq!(error.code(), Some(-5)),
+        _ => panic!("expected a failed command"),
+    }
+}
+
+#[test]
+fn mock_checked_completion_propagates_failure() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+
+    queue.fail_next(-5);
+    let (inflight, guard) = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap().enqueue_fill(&queue, 1).unwrap();
+    let (buf, error) = inflight.into_ready_checked(guard).err().expect("failed fill must not become Ready").into_parts();
+    assert!(matches!(error, ClError::CommandFailed { .. }) && error.code() == Some(-5));
+
+    // Buffer kommt als Queued zurück und lässt sich neu initialisieren
+    let (inflight, guard) = buf.enqueue_fill(&queue, 2).unwrap();
+    let ready = inflight.into_ready_checked(guard).unwrap();
+
+    let mut out = [0u32; 4];
+    queue.fail_next(-5);
+    let (_inflight, guard) = unsafe { ready.enqueue_read(&queue, &mut out) }.unwrap();
+    assert_eq!(guard.wait().err().and_then(|e| e.code()), Some(-5));
+
+    let reset = PolicyReset::set(DropErrorPolicy::Ignore);
+    assert_eq!(drop_error_policy(), DropErrorPolicy::Ignore);
+    drop(reset);
+    assert_eq!(drop_error_policy(), DropErrorPolicy::Log);
+}
+
+#[test]
+fn mock_event_guard_can_be_awaited() {
+    let ctx = MockContext::with_delay(Duration::from_millis(5));
+    let queue = MockQueue::new(&ctx);
+    let host = vec![1.5f32; 64];
+
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<f32, Queued, Mock>::new_in(&ctx, 64).unwrap()
+            .enqueue_write(&queue, &host)
+    }
+    .unwrap();
+    let guard = block_on(guard).unwrap();
+    let _ready: GpuBuffer<f32, Ready, Mock> = inflight.into_ready(guard);
+}
+
+#[test]
+fn mock_split_uploads_parts_and_rejoins() {
+    let ctx = MockContext::with_delay(Duration::from_millis(1));
+    let queue = MockQueue::new(&ctx);
+    let host: Vec<u32> = (0..1000).collect();
+
+    let buf = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, host.len()).unwrap();
+    assert_eq!(buf.split_align().unwrap(), 32);
+    let (split, parts) = buf.split_even(4).unwrap();
+    let ranges: Vec<_> = split.ranges().collect();
+    assert_eq!(ranges, [0..256, 256..512, 512..768, 768..1000]);
+
+    // alle Teile gleichzeitig in flight
+    let pending: Vec<_> = parts.into_iter().zip(&ranges)
+        .map(|(part, r)| unsafe { part.enqueue_write(&queue, &host[r.clone()]) }.unwrap())
+        .collect();
+    let ready = pending.into_iter().map(|(part, g)| part.into_ready(g)).collect();
+    let whole = split.join(ready).unwrap();
+
+    let mut out = vec![0u32; host.len()];
+    let (inflight, guard) = unsafe { whole.enqueue_read(&queue, &mut out) }.unwrap();
+    let _whole = inflight.into_ready(guard);
+    assert_eq!(out, host);
+}
+
+#[test]
+fn mock_split_rejects_misaligned_origin() {
+    let ctx = MockContext::new().base_align(64);
+    let buf = GpuBuffer::<f32, Queued, Mock>::new_in(&ctx, 64).unwrap();
+
+    let err = buf.split(&[10, 54]).err().unwrap();
+    assert!(matches!(err.error, ClError::Misaligned { offset: 40, align: 64 }));
+    assert!(err.buffer.split(&[16, 48]).is_ok());
+}
+
+#[test]
+fn mock_join_checks_order() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+    let host = [7u8; 256];
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u8, Queued, Mock>::new_in(&ctx, 256).unwrap()
+            .enqueue_write(&queue, &host)
+    }
+    .unwrap();
+    let (split, mut parts) = inflight.into_ready(guard).split(&[128, 128]).unwrap();
+
+    parts.swap(0, 1);
+    let err = split.join(parts).err().unwrap();
+    assert!(matches!(err.error, ClError::SubBufferMismatch { index: 0 }));
+
+    let (split, mut parts) = err.buffer;
+    parts.swap(0, 1);
+    // Teile eines anderen, gleich geformten Splits passen nicht
+    let (other, guard) = GpuBuffer::<u8, Queued, Mock>::new_in(&ctx, 256).unwrap().enqueue_fill(&queue, 0).unwrap();
+    let (other, foreign) = other.into_ready(guard).split(&[128, 128]).unwrap();
+    let err = split.join(foreign).err().unwrap();
+    assert!(matches!(err.error, ClError::SubBufferMismatch { index: 0 }));
+    let (split, foreign) = err.buffer;
+    drop((other, foreign));
+    assert_eq!(split.join(parts).unwrap().len(), 256);
+}
+
+#[test]
+fn mock_fill_and_copy_stay_on_device() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
 
-    //Transition to InFlight state by moving the buffer
-    let inflight: DummyGpuBuffer<InFlight> = DummyGpuBuffer {
-        buf:    queued.buf,
-        len:    queued.len,
-        _state: PhantomData,
+    let (inflight, guard) = GpuBuffer::<f32, Queued, Mock>::new_in(&ctx, 8).unwrap()
+        .enqueue_fill(&queue, 1.0)
+        .unwrap();
+    let (inflight, guard) = inflight.into_ready(guard).enqueue_fill_range(&queue, 2..4, 3.0).unwrap();
+    let src = inflight.into_ready(guard);
+
+    let dst = GpuBuffer::<f32, Queued, Mock>::new_in(&ctx, 8).unwrap();
+    let (pair, guard) = src.enqueue_copy_to(&queue, dst).unwrap();
+    let (src, dst) = pair.into_ready(guard);
+
+    // Halo-Bereich verschieben: 2..4 → 6..8
+    let (pair, guard) = src.enqueue_copy_range_to(&queue, 2..4, dst, 6).unwrap();
+    let (_src, dst) = pair.into_ready(guard);
+
+    let mut out = [0f32; 8];
+    let (inflight, guard) = unsafe { dst.enqueue_read(&queue, &mut out) }.unwrap();
+    let dst = inflight.into_ready(guard);
+    assert_eq!(out, [1.0, 1.0, 3.0, 3.0, 1.0, 1.0, 3.0, 3.0]);
+
+    let other = GpuBuffer::<f32, Queued, Mock>::new_in(&ctx, 4).unwrap();
+    let err = dst.enqueue_copy_range_to(&queue, 0..4, other, 2).err().unwrap();
+    assert!(matches!(err.error, ClError::OutOfRange { offset: 2, count: 4, len: 4 }));
+}
+
+#[test]
+fn mock_rect_moves_single_columns() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+    let (w, h) = (4, 3);
+    let grid: Vec<u32> = (0..(w * h) as u32).collect();
+
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, w * h).unwrap()
+            .enqueue_write(&queue, &grid)
+    }
+    .unwrap();
+    let ready = inflight.into_ready(guard);
+
+    // rechte Randspalte in einen dichten Host-Vektor
+    let right = Rect::d2([w - 1, 0], [1, h], w);
+    let mut col = vec![0u32; h];
+    let (inflight, guard) = unsafe { ready.enqueue_read_rect(&queue, right, &mut col, right.packed()) }.unwrap();
+    let ready = inflight.into_ready(guard);
+    assert_eq!(col, [3, 7, 11]);
+
+    // als linke Halo-Spalte zurückschreiben, dann Device-seitig kopieren
+    let left = Rect::d2([0, 0], [1, h], w);
+    let (inflight, guard) = unsafe { ready.enqueue_write_rect(&queue, left, &col, left.packed()) }.unwrap();
+    let ready = inflight.into_ready(guard);
+    let dst = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, w * h).unwrap()
+        .enqueue_fill(&queue, 0).map(|(b, g)| b.into_ready(g)).unwrap();
+    let (pair, guard) = ready.enqueue_copy_rect_to(&queue, left, dst, Rect::d2([1, 0], [1, h], w)).unwrap();
+    let (ready, dst) = pair.into_ready(guard);
+
+    let mut out = vec![0u32; w * h];
+    let (inflight, guard) = unsafe { dst.enqueue_read(&queue, &mut out) }.unwrap();
+    let _dst = inflight.into_ready(guard);
+    assert_eq!(out, [0, 3, 0, 0, 0, 7, 0, 0, 0, 11, 0, 0]);
+
+    let err = unsafe { ready.enqueue_read_rect(&queue, right, &mut col, Rect::d2([0, 0], [h, 1], h)) }.err().unwrap();
+    assert!(matches!(err.error, ClError::RectMismatch { src: [1, 3, 1], dst: [3, 1, 1] }));
+    let err = unsafe { err.buffer.enqueue_read_rect(&queue, Rect::d2([w, 0], [1, h], w), &mut col, right.packed()) }.err().unwrap();
+    assert!(matches!(err.error, ClError::OutOfRange { offset: 4, .. }));
+
+    // Überlauf der Indexrechnung und Slice-Pitch kein Vielfaches des Zeilen-Pitch
+    let huge = Rect::d3([0, 0, 2], [1, 1, 1], 1, usize::MAX / 2 + 1);
+    let err = unsafe { err.buffer.enqueue_read_rect(&queue, huge, &mut col[..1], Rect::d2([0, 0], [1, 1], 1)) }.err().unwrap();
+    assert!(matches!(err.error, ClError::InvalidRect { .. }));
+    let skewed = Rect::d3([0, 0, 0], [1, 1, 2], 2, 3);
+    let err = unsafe { err.buffer.enqueue_read_rect(&queue, skewed, &mut col[..2], skewed.packed()) }.err().unwrap();
+    assert!(matches!(err.error, ClError::InvalidRect { row_pitch: 2, slice_pitch: 3, .. }));
+}
+
+#[test]
+fn mock_map_views_round_trip() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+
+    let ready = GpuBuffer::<u64, Queued, Mock>::new_in(&ctx, 6).unwrap()
+        .enqueue_fill(&queue, 2)
+        .map(|(b, g)| b.into_ready(g))
+        .unwrap();
+
+    let mut view = ready.map_write(&queue).unwrap();
+    assert_eq!(&view[..], [2; 6]);
+    view[1] = 7;
+    view[4] = 9;
+    let ready = view.unmap().unwrap();
+
+    let view = ready.map_read(&queue).unwrap();
+    assert_eq!(&view[..], [2, 7, 2, 2, 9, 2]);
+    let _ready: GpuBuffer<u64, Ready, Mock> = view.unmap().unwrap();
+}
+
+#[test]
+fn mock_pinned_host_buffer_feeds_transfers() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+
+    let mut src = PinnedHostBuffer::<i32, Mock>::new_in(&ctx, &queue, 32).unwrap();
+    assert!(src.iter().all(|&x| x == 0));
+    src.iter_mut().enumerate().for_each(|(i, x)| *x = i as i32 - 16);
+    let mut dst = PinnedHostBuffer::<i32, Mock>::new_in(&ctx, &queue, 32).unwrap();
+
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<i32, Queued, Mock>::new_in(&ctx, 32).unwrap()
+            .enqueue_write(&queue, &src)
+    }
+    .unwrap();
+    let (inflight, guard) = unsafe { inflight.into_ready(guard).enqueue_read(&queue, &mut dst) }.unwrap();
+    let _ready = inflight.into_ready(guard);
+    assert_eq!(&src[..], &dst[..]);
+}
+
+#[test]
+fn mock_access_flags_allow_permitted_paths() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+    let host = [4u16; 8];
+
+    // Host lädt nur hoch, Device-Scratch ohne Host-Zugriff, Host liest nur
+    let (inflight, guard) = unsafe {
+        GpuBuffer::<u16, Queued, Mock, HostWriteOnly<ReadOnly>>::new_in(&ctx, 8).unwrap()
+            .enqueue_write(&queue, &host)
+    }
+    .unwrap();
+    let input = inflight.into_ready(guard);
+    let scratch = GpuBuffer::<u16, Queued, Mock, HostNoAccess>::new_in(&ctx, 8).unwrap();
+    let (pair, guard) = input.enqueue_copy_to(&queue, scratch).unwrap();
+    let (_input, scratch) = pair.into_ready(guard);
+    let result = GpuBuffer::<u16, Queued, Mock, HostReadOnly<WriteOnly>>::new_in(&ctx, 8).unwrap();
+    let (pair, guard) = scratch.enqueue_copy_to(&queue, result).unwrap();
+    let (_scratch, result) = pair.into_ready(guard);
+
+    let mut out = [0u16; 8];
+    let (inflight, guard) = unsafe { result.enqueue_read(&queue, &mut out) }.unwrap();
+    let _result = inflight.into_ready(guard);
+    assert_eq!(out, host);
+
+    let mut backing = vec![1u16, 2, 3, 4];
+    let wrapped = unsafe { GpuBuffer::<u16, Ready, Mock, UseHostPtr<'_>>::from_host(&ctx, &mut backing) }.unwrap();
+    let view = wrapped.map_read(&queue).unwrap();
+    assert_eq!(&view[..], [1, 2, 3, 4]);
+}
+
+#[test]
+fn mock_deps_order_commands_across_queues() {
+    let ctx = MockContext::with_delay(Duration::from_millis(10));
+    let (transfer, compute) = (MockQueue::new(&ctx), MockQueue::new(&MockContext::new()));
+    let host: Vec<u32> = (0..32).collect();
+    let mut out = vec![0u32; 32];
+
+    let (inflight, upload) = unsafe {
+        GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 32).unwrap()
+            .enqueue_write(&transfer, &host)
+    }
+    .unwrap();
+    // Guard eines anderen Buffers: der Read hinge am falschen Event
+    let (other, fill) = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 32).unwrap().enqueue_fill(&compute, 0).unwrap();
+    let err = unsafe { inflight.enqueue_read_after(&fill, &compute, &mut out, &[]) }.err().unwrap();
+    assert!(matches!(err.error, ClError::GuardMismatch));
+    let (inflight, _other) = (err.buffer, other.into_ready(fill));
+
+    // ohne Warten weiterreichen: der Read hängt am Upload-Event
+    let (inflight, guard) = unsafe { inflight.enqueue_read_after(&upload, &compute, &mut out, &[]) }.unwrap();
+    let ready = inflight.into_ready(guard);
+    drop(upload);
+    assert_eq!(out, host);
+
+    // fehlgeschlagene Abhängigkeit → abhängiges Kommando läuft nicht
+    transfer.fail_next(-5);
+    let (inflight, upload) = unsafe { ready.enqueue_write_at(&transfer, 0, &host) }.unwrap();
+    let (inflight, guard) = unsafe {
+        inflight
+            .enqueue_read_range_after(&upload, &compute, 0..4, &mut out[..4], &[])
+    }
+    .unwrap();
+    compute.finish();
+    match inflight.try_complete(guard) {
+        Err(NotReady::Failed { error, .. }) => assert_eq!(error.code(), Some(-14)),
+        _ => panic!("dependent command must fail"),
+    }
+    drop(upload);
+}
+
+#[test]
+fn mock_stream_pair_orders_across_queues() {
+    let ctx = MockContext::with_delay(Duration::from_millis(20));
+    let streams = StreamPair::<Mock>::new(MockQueue::new(&ctx), MockQueue::new(&MockContext::new()));
+    let host: Vec<u32> = (0..16).collect();
+    let mut out = vec![0u32; 16];
+
+    let (a, upload) = unsafe { streams.write(GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 16).unwrap(), &host) }.unwrap();
+    // anderer Buffer: Fill überholt den verzögerten Upload
+    let (_c, fill) = streams.fill(GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 16).unwrap(), 0).unwrap();
+    streams.compute().finish();
+    assert_eq!(fill.is_complete().ok(), Some(true));
+    assert_eq!(upload.is_complete().ok(), Some(false));
+
+    // gleicher Buffer: Copy wartet auf den Upload, Read auf die Copy
+    let b = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 16).unwrap();
+    let ((_a, b), copy) = streams.copy(a, b).unwrap();
+    let (_b, read) = unsafe { streams.read(b, &mut out) }.unwrap();
+    drop((upload, copy, read));
+    assert_eq!(out, host);
+}
+
+#[test]
+fn mock_stream_pair_into_ready_waits_for_last_command() {
+    let ctx = MockContext::with_delay(Duration::from_millis(20));
+    let streams = StreamPair::<Mock>::new(MockQueue::new(&ctx), MockQueue::new(&MockContext::new()));
+    let host: Vec<u32> = (0..8).collect();
+
+    // Paired verlässt das StreamPair nur über into_ready, das auf den Upload wartet
+    let (buf, upload) = unsafe { streams.write(GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 8).unwrap(), &host) }.unwrap();
+    let ready = streams.into_ready(buf).unwrap();
+    assert_eq!(upload.is_complete().ok(), Some(true));
+    let view = ready.map_read(streams.transfer()).unwrap();
+    assert_eq!(&view[..], host);
+}
+
+#[test]
+fn mock_stream_pair_rejects_foreign_and_keeps_failed_commands() {
+    let ctx = MockContext::new();
+    let streams = StreamPair::<Mock>::new(MockQueue::new(&ctx), MockQueue::new(&ctx));
+    let other = StreamPair::<Mock>::new(MockQueue::new(&ctx), MockQueue::new(&ctx));
+    let host = vec![1u32; 8];
+
+    // Paired eines anderen Paars: dessen Upload ist hier unbekannt
+    let (buf, upload) = unsafe { streams.write(GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 8).unwrap(), &host) }.unwrap();
+    let buf = match other.into_ready(buf) {
+        Err(e) => { assert!(matches!(e.error, ClError::PairMismatch)); e.buffer }
+        Ok(_)  => panic!("foreign pair must not release the buffer"),
+    };
+    let buf = match other.fill(buf, 0) {
+        Err(e) => { assert!(matches!(e.error, ClError::PairMismatch)); e.buffer }
+        Ok(_)  => panic!("foreign pair must not enqueue on the buffer"),
     };
+    upload.wait().unwrap();
 
-    // Define a dummy guard struct to simulate GPU-event guard
-    struct DummyGuard;
-    impl Drop for DummyGuard {
-        fn drop(&mut self) {
-            // This drop could perform synchronization in a real implementation
-        }
+    // fehlgeschlagener Upload bleibt vermerkt, auch wenn er schon beendet ist
+    streams.transfer().fail_next(-5);
+    let (buf, upload) = unsafe { streams.write(buf, &host) }.unwrap();
+    assert!(upload.wait().is_err());
+    let (buf, fill) = streams.fill(buf, 0).unwrap();
+    assert!(fill.wait().is_err());
+    assert!(streams.into_ready(buf).is_ok());
+}
+
+#[test]
+fn mock_stream_pair_orders_sub_buffers_after_parent() {
+    let ctx = MockContext::with_delay(Duration::from_millis(20));
+    let streams = StreamPair::<Mock>::new(MockQueue::new(&ctx), MockQueue::new(&MockContext::new()));
+    let host: Vec<u32> = (0..64).collect();
+    let mut out = vec![0u32; 32];
+
+    let (whole, upload) = unsafe { streams.write(GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 64).unwrap(), &host) }.unwrap();
+    // Sub-Buffer hat eine eigene mem_id, die Copy wartet trotzdem auf den Upload des Parents
+    let (_split, mut parts) = whole.split_even(2).unwrap();
+    let upper = parts.pop().unwrap();
+    let dst = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 32).unwrap();
+    let ((_upper, dst), copy) = streams.copy(upper, dst).unwrap();
+    let (_dst, read) = unsafe { streams.read(dst, &mut out) }.unwrap();
+    drop((upload, copy, read));
+    assert_eq!(out, host[32..]);
+}
+
+#[test]
+fn mock_pipeline_streams_uneven_chunks() {
+    let ctx = MockContext::with_delay(Duration::from_millis(1));
+    let streams = StreamPair::<Mock>::new(MockQueue::new(&ctx), MockQueue::new(&ctx));
+    let input: Vec<u32> = (0..10).collect();
+    let mut output = vec![0u32; 10];
+
+    let mut lens = Vec::new();
+    Pipeline::new(3).depth(2).run(&streams, &ctx, &input, &mut output, |s, len, buf_in, buf_out| {
+        lens.push(len);
+        Ok(s.copy(buf_in, buf_out)?)
+    }).unwrap();
+    assert_eq!(lens, [3, 3, 3, 1]);
+    assert_eq!(output, input);
+
+    // Abbruch nach zwei Chunks: Rest bleibt unberührt
+    let mut output = vec![0u32; 10];
+    let mut chunks = 0;
+    let done = Pipeline::new(3).run_until(&streams, &ctx, &input, &mut output, || { chunks += 1; chunks > 2 },
+        |s, _, buf_in, buf_out| Ok(s.copy(buf_in, buf_out)?)).unwrap();
+    assert_eq!(done, 6);
+    assert_eq!(output[..6], input[..6]);
+    assert_eq!(output[6..], [0; 4]);
+
+    // Timeout: kehrt zurück, obwohl Kommandos noch laufen; `output` bleibt unberührt
+    let slow = MockContext::with_delay(Duration::from_millis(100));
+    let streams = StreamPair::<Mock>::new(MockQueue::new(&slow), MockQueue::new(&slow));
+    let mut output = vec![0u32; 10];
+    let t = Instant::now();
+    let err = Pipeline::new(3).timeout(Duration::from_millis(10))
+        .run(&streams, &slow, &input, &mut output, |s, _, buf_in, buf_out| Ok(s.copy(buf_in, buf_out)?))
+        .unwrap_err();
+    assert!(matches!(err, ClError::Timeout));
+    assert!(t.elapsed() < Duration::from_millis(100));
+    assert_eq!(output, [0; 10]);
+}
+
+#[test]
+fn mock_wait_timeout_hands_back_pending_buffer() {
+    let ctx = MockContext::with_delay(Duration::from_millis(50));
+    let queue = MockQueue::new(&ctx);
+
+    let (inflight, guard) = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap().enqueue_fill(&queue, 1).unwrap();
+    let Err(NotReady::Pending { buffer, mut guard }) = inflight.into_ready_timeout(guard, Duration::from_millis(1)) else {
+        panic!("delayed command must time out");
+    };
+    // Polling mit kurzen Timeouts teilt sich ein Completion-Signal pro Guard
+    let mut polls = 0;
+    while !guard.wait_timeout(Duration::from_millis(1)).unwrap() {
+        polls += 1;
+        assert!(polls < 5_000, "command never completed");
+    }
+    assert!(guard.wait_timeout(Duration::ZERO).unwrap());
+    assert!(buffer.into_ready_timeout(guard, Duration::ZERO).is_ok());
+
+    queue.fail_next(-5);
+    let (inflight, guard) = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap().enqueue_fill(&queue, 1).unwrap();
+    match inflight.into_ready_timeout(guard, Duration::from_secs(5)) {
+        Err(NotReady::Failed { error, .. }) => assert_eq!(error.code(), Some(-5)),
+        _ => panic!("expected a failed command"),
     }
-    let guard = DummyGuard;
+}
 
-    // After the guard is dropped, transition to Ready state
-    let _ready: DummyGpuBuffer<Ready> = DummyGpuBuffer {
-        buf:    inflight.buf,
-        len:    inflight.len,
-        _state: PhantomData,
+#[test]
+fn mock_ping_pong_swaps_after_each_step() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+    let ready = |v: u32| {
+        let (buf, guard) = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap().enqueue_fill(&queue, v).unwrap();
+        buf.into_ready(guard)
     };
 
-    // Explicitly drop the guard to complete the Ready transition
+    let pp = PingPong::new(ready(1), ready(0)).unwrap();
+    let first = Mock::mem_id(pp.src().raw());
+    // „Kernel“: dst = src, dann Rollentausch
+    let pp = pp.step_with(|src, dst| Ok(src.enqueue_copy_to(&queue, dst)?)).unwrap();
+    assert_eq!(Mock::mem_id(pp.dst().raw()), first);
+
+    let (src, _dst) = pp.into_parts();
+    let mut out = [0u32; 4];
+    let (inflight, guard) = unsafe { src.enqueue_read(&queue, &mut out) }.unwrap();
+    inflight.into_ready(guard);
+    assert_eq!(out, [1; 4]);
+
+    let err = PingPong::new(ready(1), GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 2).unwrap()
+        .enqueue_fill(&queue, 0).map(|(b, g)| b.into_ready(g)).unwrap()).err().unwrap();
+    assert!(matches!(err.error, ClError::SizeMismatch { expected: 4, actual: 2 }));
+
+    // step_with gibt fremde Buffer anderer Länge zurück
+    let pp = PingPong::new(ready(1), ready(0)).unwrap();
+    let short = || GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 2).unwrap().enqueue_fill(&queue, 0).map(|(b, g)| b.into_ready(g)).unwrap();
+    let err = pp.step_with(|_, _| Ok(short().enqueue_copy_to(&queue, short())?)).err().unwrap();
+    assert!(matches!(err, ClError::InvalidArgs { .. }));
+}
+
+#[test]
+fn mock_pool_reuses_size_classes_and_evicts_lru() {
+    let ctx = MockContext::new();
+    let queue = MockQueue::new(&ctx);
+    // Budget für zwei 8-Element-u32-Allokationen
+    let pool = BufferPool::<u32, Mock>::new(&ctx, 64);
+
+    let a = pool.get(5).unwrap();
+    assert_eq!(a.len(), 5);
+    let id_a = Mock::mem_id(a.raw());
+    let (a, guard) = a.then(|b| b.enqueue_fill(&queue, 7)).unwrap();
+    let a = a.map(|b| b.into_ready(guard));
+    drop(a);
+    assert_eq!(pool.cached_bytes(), 32);
+
+    // 7 → gleiche Klasse (8): Treffer, Länge wie angefordert
+    let b = pool.get(7).unwrap();
+    assert_eq!((Mock::mem_id(b.raw()), b.len()), (id_a, 7));
+    assert_eq!(pool.cached_bytes(), 0);
+
+    // InFlight fallengelassen → nicht zurück in den Pool
+    let (b, guard) = b.then(|b| b.enqueue_fill(&queue, 0)).unwrap();
+    drop(b);
     drop(guard);
+    assert_eq!(pool.cached_bytes(), 0);
+
+    let (c, d, e) = (pool.get(8).unwrap(), pool.get(8).unwrap(), pool.get(8).unwrap());
+    let id_d = Mock::mem_id(d.raw());
+    let id_e = Mock::mem_id(e.raw());
+    drop((c, d, e));
+    // c war am längsten unbenutzt und wurde verdrängt, e kommt zuerst
+    assert_eq!(pool.cached_bytes(), 64);
+    assert_eq!(Mock::mem_id(pool.get(8).unwrap().into_inner().raw()), id_e);
+    assert_eq!(Mock::mem_id(pool.get(8).unwrap().raw()), id_d);
+
+    let exact = BufferPool::<u32, Mock>::new(&ctx, 1024).size_class(SizeClass::Exact);
+    drop(exact.get(5).unwrap());
+    // 6 ≠ 5: Fehlschlag, die 5er-Allokation bleibt im Pool
+    let six = exact.get(6).unwrap();
+    assert_eq!(exact.cached_bytes(), 20);
+    drop(six);
+    assert_eq!(exact.cached_bytes(), 44);
+    assert_eq!(SizeClass::Multiple(64).round(65), Some(128));
+    assert_eq!(SizeClass::PowerOfTwo.round(usize::MAX / 2 + 2), None);
+    assert_eq!(pool.get(usize::MAX / 2 + 2).err().and_then(|e| e.code()), Some(-61));
+}
+
+#[test]
+fn mock_pool_releases_oversized_allocation_without_flushing() {
+    let ctx = MockContext::new();
+    let pool = BufferPool::<u32, Mock>::new(&ctx, 64);
+
+    let (small, big) = (pool.get(8).unwrap(), pool.get(32).unwrap());
+    let id_small = Mock::mem_id(small.raw());
+    drop(small);
+    // 32 Elemente = 128 Bytes > Budget: direkt freigeben, der Cache bleibt
+    drop(big);
+    assert_eq!(pool.cached_bytes(), 32);
+    assert_eq!(Mock::mem_id(pool.get(8).unwrap().raw()), id_small);
+}
+
+#[test]
+fn mock_dropping_device_guards_does_not_block() {
+    let ctx = MockContext::with_delay(Duration::from_millis(20));
+    let queue = MockQueue::new(&ctx);
+
+    let (x, gx) = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap().enqueue_fill(&queue, 1).unwrap();
+    let (y, gy) = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap().enqueue_fill(&queue, 2).unwrap();
+    // Guard und Buffer des Fills fallen lassen: kein Warten, Speicher bleibt bis zum Ende am Leben
+    drop(gy);
+    drop(y);
+    let Err(NotReady::Pending { buffer: x, guard: gx }) = x.try_complete(gx) else {
+        panic!("dropping a device-only guard must not wait");
+    };
+
+    // Host-Transfer: Drop des Guards wartet weiterhin auf das Event
+    let mut out = [0u32; 4];
+    let (x, g) = unsafe { x.enqueue_read_after(&gx, &queue, &mut out, &[]) }.unwrap();
+    drop((g, x));
+    assert_eq!(out, [1; 4]);
+    assert!(gx.is_complete().unwrap());
+}
+
+#[test]
+fn mock_host_ptr_guards_wait_on_drop() {
+    let delay = Duration::from_millis(20);
+    let ctx = MockContext::with_delay(delay);
+    let queue = MockQueue::new(&ctx);
+    let mut backing = vec![0u32; 4];
+
+    // Fill auf UseHostPtr-Speicher: Drop des Guards wartet wie bei Host-Transfers
+    let wrapped = unsafe { GpuBuffer::<u32, Ready, Mock, UseHostPtr<'_>>::from_host(&ctx, &mut backing) }.unwrap();
+    let start = Instant::now();
+    let (x, g) = wrapped.enqueue_fill(&queue, 7).unwrap();
+    drop(x);
+    drop(g);
+    assert!(start.elapsed() >= delay);
+    drop(backing);
+}
+
+#[test]
+fn mock_transfer_scope_waits_before_returning() {
+    let ctx = MockContext::with_delay(Duration::from_millis(20));
+    let queue = MockQueue::new(&ctx);
+    let mut out = [0u32; 4];
+
+    let (ready, fill) = GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap().enqueue_fill(&queue, 3).unwrap();
+    let ready = ready.into_ready(fill);
+    // Guard verlässt den Scope: das Warten hängt trotzdem nicht an seinem Drop
+    let (inflight, guard) = transfer_scope(|s| s.read(ready, &queue, &mut out).unwrap());
+    assert_eq!(guard.is_complete().ok(), Some(true));
+    let _ready = inflight.into_ready(guard);
+    assert_eq!(out, [3; 4]);
+}
+
+#[test]
+fn mock_guard_set_joins_many_transfers() {
+    let ctx = MockContext::with_delay(Duration::from_millis(1));
+    let queue = MockQueue::new(&ctx);
+    let host: Vec<u32> = (0..16).collect();
+
+    let mut pending = GuardSet::with_capacity(4);
+    for chunk in host.chunks(4) {
+        pending.push(unsafe { GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap().enqueue_write(&queue, chunk) }.unwrap());
+    }
+    assert_eq!(pending.len(), 4);
+    let mut ready: Vec<GpuBuffer<u32, Ready, Mock>> = pending.join_checked().unwrap();
+    let last = ready.pop().unwrap();
+
+    // heterogen: verschiedene Elementtypen, je ein Guard
+    let ((_last, copy), b) = join((
+        last.enqueue_copy_to(&queue, GpuBuffer::<u32, Queued, Mock>::new_in(&ctx, 4).unwrap()).unwrap(),
+        GpuBuffer::<u8, Queued, Mock>::new_in(&ctx, 2).unwrap().enqueue_fill(&queue, 7).unwrap(),
+    ));
+    let (mut out, mut bytes) = ([0u32; 4], [0u8; 2]);
+    join_checked((unsafe { copy.enqueue_read(&queue, &mut out) }.unwrap(), unsafe { b.enqueue_read(&queue, &mut bytes) }.unwrap())).unwrap();
+    assert_eq!((out, bytes), ([12, 13, 14, 15], [7, 7]));
+
+    queue.fail_next(-5);
+    let failing: GuardSet<_, Mock> = ready.into_iter().map(|b| b.enqueue_fill(&queue, 0).unwrap()).collect();
+    assert_eq!(failing.join_checked().err().and_then(|e| e.code()), Some(-5));
 }